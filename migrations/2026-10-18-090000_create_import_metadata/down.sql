@@ -0,0 +1 @@
+DROP TABLE lecsicon_metadata
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS lecsicon_metadata (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        source_path TEXT NOT NULL,
+        source_sha256 TEXT NOT NULL,
+        rows_read INTEGER NOT NULL,
+        rows_total INTEGER NOT NULL,
+        imported_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        crate_version TEXT NOT NULL,
+        schema_version INTEGER NOT NULL
+);
@@ -0,0 +1,2 @@
+ALTER TABLE lecsicon_metadata DROP COLUMN rows_rejected;
+DROP TABLE lecsicon_rejects
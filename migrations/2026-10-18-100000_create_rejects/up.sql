@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS lecsicon_rejects (
+        id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+        line INTEGER NOT NULL,
+        raw_line TEXT NOT NULL,
+        reason TEXT NOT NULL
+);
+ALTER TABLE lecsicon_metadata ADD COLUMN rows_rejected INTEGER NOT NULL DEFAULT 0;
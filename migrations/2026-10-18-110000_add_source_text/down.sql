@@ -0,0 +1,3 @@
+ALTER TABLE lecsicon DROP COLUMN extra_fields;
+ALTER TABLE lecsicon DROP COLUMN raw_features;
+ALTER TABLE lecsicon DROP COLUMN source_line;
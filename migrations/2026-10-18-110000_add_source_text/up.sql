@@ -0,0 +1,3 @@
+ALTER TABLE lecsicon ADD COLUMN source_line INTEGER;
+ALTER TABLE lecsicon ADD COLUMN raw_features TEXT;
+ALTER TABLE lecsicon ADD COLUMN extra_fields TEXT;
@@ -0,0 +1 @@
+ALTER TABLE lecsicon DROP COLUMN other_features;
@@ -0,0 +1 @@
+ALTER TABLE lecsicon ADD COLUMN other_features TEXT;
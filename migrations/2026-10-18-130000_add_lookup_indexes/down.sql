@@ -0,0 +1 @@
+DROP INDEX lecsicon_word;
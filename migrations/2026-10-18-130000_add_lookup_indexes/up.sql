@@ -0,0 +1 @@
+CREATE INDEX lecsicon_word ON lecsicon (word);
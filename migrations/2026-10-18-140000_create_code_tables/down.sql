@@ -0,0 +1,58 @@
+DROP VIEW lecsicon_readable;
+CREATE TABLE lecsicon_rebuilt (
+        id INTEGER NOT NULL,
+        word TEXT NOT NULL,
+        lemma TEXT,
+        tag INTEGER,
+        adposition_type BOOLEAN,
+        definite BOOLEAN,
+        degree INTEGER,
+        gender INTEGER,
+        mood INTEGER,
+        mutation INTEGER,
+        name_type INTEGER,
+        number INTEGER,
+        number_form BOOLEAN,
+        number_type INTEGER,
+        person INTEGER,
+        polarity BOOLEAN,
+        polite INTEGER,
+        position BOOLEAN,
+        possessive BOOLEAN,
+        pronoun_type INTEGER,
+        relative BOOLEAN,
+        style INTEGER,
+        tense INTEGER,
+        verb_form BOOLEAN,
+        source_line INTEGER,
+        raw_features TEXT,
+        extra_fields TEXT,
+        other_features TEXT,
+        PRIMARY KEY (id, word)
+);
+INSERT INTO lecsicon_rebuilt (id, word, lemma, tag, adposition_type, definite, degree, gender, mood, mutation, name_type, number, number_form, number_type, person, polarity, polite, position, possessive, pronoun_type, relative, style, tense, verb_form, source_line, raw_features, extra_fields, other_features)
+        SELECT id, word, lemma, tag, adposition_type, definite, degree, gender, mood, mutation, name_type, number, number_form, number_type, person, polarity, polite, position, possessive, pronoun_type, relative, style, tense, verb_form, source_line, raw_features, extra_fields, other_features FROM lecsicon;
+DROP TABLE lecsicon;
+ALTER TABLE lecsicon_rebuilt RENAME TO lecsicon;
+CREATE INDEX lecsicon_word ON lecsicon (word);
+DROP TABLE verb_form_codes;
+DROP TABLE tense_codes;
+DROP TABLE style_codes;
+DROP TABLE relative_codes;
+DROP TABLE pronoun_type_codes;
+DROP TABLE possessive_codes;
+DROP TABLE position_codes;
+DROP TABLE polite_codes;
+DROP TABLE polarity_codes;
+DROP TABLE person_codes;
+DROP TABLE number_type_codes;
+DROP TABLE number_form_codes;
+DROP TABLE number_codes;
+DROP TABLE name_type_codes;
+DROP TABLE mutation_codes;
+DROP TABLE mood_codes;
+DROP TABLE gender_codes;
+DROP TABLE degree_codes;
+DROP TABLE definite_codes;
+DROP TABLE adposition_type_codes;
+DROP TABLE tag_codes;
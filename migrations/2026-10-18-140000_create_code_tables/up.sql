@@ -0,0 +1,253 @@
+CREATE TABLE tag_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO tag_codes (code, label) VALUES
+        (0, 'ADJ'),
+        (1, 'ADP'),
+        (2, 'ADV'),
+        (3, 'CONJ'),
+        (4, 'DET'),
+        (5, 'INTJ'),
+        (6, 'NOUN'),
+        (7, 'NUM'),
+        (8, 'PART'),
+        (9, 'PRON'),
+        (10, 'PROPN'),
+        (11, 'VERB');
+CREATE TABLE adposition_type_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO adposition_type_codes (code, label) VALUES
+        (1, 'Prep');
+CREATE TABLE definite_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO definite_codes (code, label) VALUES
+        (1, 'Def');
+CREATE TABLE degree_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO degree_codes (code, label) VALUES
+        (0, 'Cmp'),
+        (1, 'Equ'),
+        (2, 'Pos'),
+        (3, 'Sup');
+CREATE TABLE gender_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO gender_codes (code, label) VALUES
+        (0, 'Fem'),
+        (1, 'Fem,Masc'),
+        (2, 'Masc');
+CREATE TABLE mood_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO mood_codes (code, label) VALUES
+        (0, 'Imp'),
+        (1, 'Ind'),
+        (2, 'Sub');
+CREATE TABLE mutation_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO mutation_codes (code, label) VALUES
+        (0, 'AM'),
+        (1, 'HM'),
+        (2, 'NM'),
+        (3, 'SM');
+CREATE TABLE name_type_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO name_type_codes (code, label) VALUES
+        (0, 'Geo'),
+        (1, 'Prs');
+CREATE TABLE number_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO number_codes (code, label) VALUES
+        (0, 'Coll'),
+        (1, 'Plur'),
+        (2, 'Sing');
+CREATE TABLE number_form_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO number_form_codes (code, label) VALUES
+        (1, 'Word');
+CREATE TABLE number_type_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO number_type_codes (code, label) VALUES
+        (0, 'Card'),
+        (1, 'Ord');
+CREATE TABLE person_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO person_codes (code, label) VALUES
+        (0, '1'),
+        (1, '3'),
+        (2, '2'),
+        (3, '0');
+CREATE TABLE polarity_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO polarity_codes (code, label) VALUES
+        (1, 'Neg');
+CREATE TABLE polite_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO polite_codes (code, label) VALUES
+        (0, 'Form'),
+        (1, 'Inf');
+CREATE TABLE position_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO position_codes (code, label) VALUES
+        (1, 'Prenom');
+CREATE TABLE possessive_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO possessive_codes (code, label) VALUES
+        (1, 'Yes');
+CREATE TABLE pronoun_type_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO pronoun_type_codes (code, label) VALUES
+        (0, 'Art'),
+        (1, 'Dem'),
+        (2, 'Int'),
+        (3, 'Neg'),
+        (4, 'Prs'),
+        (5, 'Tot'),
+        (6, 'Rel');
+CREATE TABLE relative_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO relative_codes (code, label) VALUES
+        (1, 'Rel');
+CREATE TABLE style_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO style_codes (code, label) VALUES
+        (0, 'Arch'),
+        (1, 'Coll'),
+        (2, 'Form');
+CREATE TABLE tense_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO tense_codes (code, label) VALUES
+        (0, 'Fut'),
+        (1, 'Imp'),
+        (2, 'Past'),
+        (3, 'Pqp'),
+        (4, 'Pres');
+CREATE TABLE verb_form_codes (
+        code INTEGER PRIMARY KEY NOT NULL,
+        label TEXT NOT NULL UNIQUE
+);
+INSERT INTO verb_form_codes (code, label) VALUES
+        (1, 'Vnoun');
+CREATE TABLE lecsicon_rebuilt (
+        id INTEGER NOT NULL,
+        word TEXT NOT NULL,
+        lemma TEXT,
+        tag INTEGER REFERENCES tag_codes (code),
+        adposition_type BOOLEAN REFERENCES adposition_type_codes (code),
+        definite BOOLEAN REFERENCES definite_codes (code),
+        degree INTEGER REFERENCES degree_codes (code),
+        gender INTEGER REFERENCES gender_codes (code),
+        mood INTEGER REFERENCES mood_codes (code),
+        mutation INTEGER REFERENCES mutation_codes (code),
+        name_type INTEGER REFERENCES name_type_codes (code),
+        number INTEGER REFERENCES number_codes (code),
+        number_form BOOLEAN REFERENCES number_form_codes (code),
+        number_type INTEGER REFERENCES number_type_codes (code),
+        person INTEGER REFERENCES person_codes (code),
+        polarity BOOLEAN REFERENCES polarity_codes (code),
+        polite INTEGER REFERENCES polite_codes (code),
+        position BOOLEAN REFERENCES position_codes (code),
+        possessive BOOLEAN REFERENCES possessive_codes (code),
+        pronoun_type INTEGER REFERENCES pronoun_type_codes (code),
+        relative BOOLEAN REFERENCES relative_codes (code),
+        style INTEGER REFERENCES style_codes (code),
+        tense INTEGER REFERENCES tense_codes (code),
+        verb_form BOOLEAN REFERENCES verb_form_codes (code),
+        source_line INTEGER,
+        raw_features TEXT,
+        extra_fields TEXT,
+        other_features TEXT,
+        PRIMARY KEY (id, word)
+);
+INSERT INTO lecsicon_rebuilt (id, word, lemma, tag, adposition_type, definite, degree, gender, mood, mutation, name_type, number, number_form, number_type, person, polarity, polite, position, possessive, pronoun_type, relative, style, tense, verb_form, source_line, raw_features, extra_fields, other_features)
+        SELECT id, word, lemma, tag, adposition_type, definite, degree, gender, mood, mutation, name_type, number, number_form, number_type, person, polarity, polite, position, possessive, pronoun_type, relative, style, tense, verb_form, source_line, raw_features, extra_fields, other_features FROM lecsicon;
+DROP TABLE lecsicon;
+ALTER TABLE lecsicon_rebuilt RENAME TO lecsicon;
+CREATE INDEX lecsicon_word ON lecsicon (word);
+CREATE VIEW lecsicon_readable AS
+SELECT
+        lecsicon.id,
+        lecsicon.word,
+        lecsicon.lemma,
+        tag_codes.label AS tag,
+        adposition_type_codes.label AS adposition_type,
+        definite_codes.label AS definite,
+        degree_codes.label AS degree,
+        gender_codes.label AS gender,
+        mood_codes.label AS mood,
+        mutation_codes.label AS mutation,
+        name_type_codes.label AS name_type,
+        number_codes.label AS number,
+        number_form_codes.label AS number_form,
+        number_type_codes.label AS number_type,
+        person_codes.label AS person,
+        polarity_codes.label AS polarity,
+        polite_codes.label AS polite,
+        position_codes.label AS position,
+        possessive_codes.label AS possessive,
+        pronoun_type_codes.label AS pronoun_type,
+        relative_codes.label AS relative,
+        style_codes.label AS style,
+        tense_codes.label AS tense,
+        verb_form_codes.label AS verb_form,
+        lecsicon.other_features,
+        lecsicon.source_line
+FROM lecsicon
+LEFT JOIN tag_codes ON tag_codes.code = lecsicon.tag
+LEFT JOIN adposition_type_codes ON adposition_type_codes.code = lecsicon.adposition_type
+LEFT JOIN definite_codes ON definite_codes.code = lecsicon.definite
+LEFT JOIN degree_codes ON degree_codes.code = lecsicon.degree
+LEFT JOIN gender_codes ON gender_codes.code = lecsicon.gender
+LEFT JOIN mood_codes ON mood_codes.code = lecsicon.mood
+LEFT JOIN mutation_codes ON mutation_codes.code = lecsicon.mutation
+LEFT JOIN name_type_codes ON name_type_codes.code = lecsicon.name_type
+LEFT JOIN number_codes ON number_codes.code = lecsicon.number
+LEFT JOIN number_form_codes ON number_form_codes.code = lecsicon.number_form
+LEFT JOIN number_type_codes ON number_type_codes.code = lecsicon.number_type
+LEFT JOIN person_codes ON person_codes.code = lecsicon.person
+LEFT JOIN polarity_codes ON polarity_codes.code = lecsicon.polarity
+LEFT JOIN polite_codes ON polite_codes.code = lecsicon.polite
+LEFT JOIN position_codes ON position_codes.code = lecsicon.position
+LEFT JOIN possessive_codes ON possessive_codes.code = lecsicon.possessive
+LEFT JOIN pronoun_type_codes ON pronoun_type_codes.code = lecsicon.pronoun_type
+LEFT JOIN relative_codes ON relative_codes.code = lecsicon.relative
+LEFT JOIN style_codes ON style_codes.code = lecsicon.style
+LEFT JOIN tense_codes ON tense_codes.code = lecsicon.tense
+LEFT JOIN verb_form_codes ON verb_form_codes.code = lecsicon.verb_form;
@@ -0,0 +1,66 @@
+CREATE TABLE lemmas (
+        id INTEGER PRIMARY KEY NOT NULL,
+        lemma TEXT NOT NULL,
+        tag INTEGER REFERENCES tag_codes (code),
+        UNIQUE (lemma, tag)
+);
+INSERT INTO lemmas (lemma, tag)
+        SELECT DISTINCT lemma, tag FROM lecsicon WHERE lemma IS NOT NULL ORDER BY lemma, tag;
+ALTER TABLE lecsicon ADD COLUMN lemma_id INTEGER REFERENCES lemmas (id);
+UPDATE lecsicon SET lemma_id = (
+        SELECT lemmas.id FROM lemmas WHERE lemmas.lemma = lecsicon.lemma AND lemmas.tag IS lecsicon.tag
+);
+CREATE INDEX lecsicon_lemma_id ON lecsicon (lemma_id);
+DROP VIEW lecsicon_readable;
+ALTER TABLE lecsicon DROP COLUMN lemma;
+CREATE VIEW lecsicon_readable AS
+SELECT
+        lecsicon.id,
+        lecsicon.word,
+        lemmas.lemma,
+        tag_codes.label AS tag,
+        adposition_type_codes.label AS adposition_type,
+        definite_codes.label AS definite,
+        degree_codes.label AS degree,
+        gender_codes.label AS gender,
+        mood_codes.label AS mood,
+        mutation_codes.label AS mutation,
+        name_type_codes.label AS name_type,
+        number_codes.label AS number,
+        number_form_codes.label AS number_form,
+        number_type_codes.label AS number_type,
+        person_codes.label AS person,
+        polarity_codes.label AS polarity,
+        polite_codes.label AS polite,
+        position_codes.label AS position,
+        possessive_codes.label AS possessive,
+        pronoun_type_codes.label AS pronoun_type,
+        relative_codes.label AS relative,
+        style_codes.label AS style,
+        tense_codes.label AS tense,
+        verb_form_codes.label AS verb_form,
+        lecsicon.other_features,
+        lecsicon.source_line
+FROM lecsicon
+LEFT JOIN lemmas ON lemmas.id = lecsicon.lemma_id
+LEFT JOIN tag_codes ON tag_codes.code = lecsicon.tag
+LEFT JOIN adposition_type_codes ON adposition_type_codes.code = lecsicon.adposition_type
+LEFT JOIN definite_codes ON definite_codes.code = lecsicon.definite
+LEFT JOIN degree_codes ON degree_codes.code = lecsicon.degree
+LEFT JOIN gender_codes ON gender_codes.code = lecsicon.gender
+LEFT JOIN mood_codes ON mood_codes.code = lecsicon.mood
+LEFT JOIN mutation_codes ON mutation_codes.code = lecsicon.mutation
+LEFT JOIN name_type_codes ON name_type_codes.code = lecsicon.name_type
+LEFT JOIN number_codes ON number_codes.code = lecsicon.number
+LEFT JOIN number_form_codes ON number_form_codes.code = lecsicon.number_form
+LEFT JOIN number_type_codes ON number_type_codes.code = lecsicon.number_type
+LEFT JOIN person_codes ON person_codes.code = lecsicon.person
+LEFT JOIN polarity_codes ON polarity_codes.code = lecsicon.polarity
+LEFT JOIN polite_codes ON polite_codes.code = lecsicon.polite
+LEFT JOIN position_codes ON position_codes.code = lecsicon.position
+LEFT JOIN possessive_codes ON possessive_codes.code = lecsicon.possessive
+LEFT JOIN pronoun_type_codes ON pronoun_type_codes.code = lecsicon.pronoun_type
+LEFT JOIN relative_codes ON relative_codes.code = lecsicon.relative
+LEFT JOIN style_codes ON style_codes.code = lecsicon.style
+LEFT JOIN tense_codes ON tense_codes.code = lecsicon.tense
+LEFT JOIN verb_form_codes ON verb_form_codes.code = lecsicon.verb_form;
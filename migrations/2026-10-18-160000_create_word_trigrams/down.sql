@@ -0,0 +1,4 @@
+DROP TRIGGER lecsicon_trigrams_update;
+DROP TRIGGER lecsicon_trigrams_delete;
+DROP TRIGGER lecsicon_trigrams_insert;
+DROP TABLE lecsicon_trigrams;
@@ -0,0 +1,16 @@
+CREATE VIRTUAL TABLE lecsicon_trigrams USING fts5(
+        word,
+        content = 'lecsicon',
+        tokenize = 'trigram'
+);
+CREATE TRIGGER lecsicon_trigrams_insert AFTER INSERT ON lecsicon BEGIN
+        INSERT INTO lecsicon_trigrams (rowid, word) VALUES (new.rowid, new.word);
+END;
+CREATE TRIGGER lecsicon_trigrams_delete AFTER DELETE ON lecsicon BEGIN
+        INSERT INTO lecsicon_trigrams (lecsicon_trigrams, rowid, word) VALUES ('delete', old.rowid, old.word);
+END;
+CREATE TRIGGER lecsicon_trigrams_update AFTER UPDATE OF word ON lecsicon BEGIN
+        INSERT INTO lecsicon_trigrams (lecsicon_trigrams, rowid, word) VALUES ('delete', old.rowid, old.word);
+        INSERT INTO lecsicon_trigrams (rowid, word) VALUES (new.rowid, new.word);
+END;
+INSERT INTO lecsicon_trigrams (lecsicon_trigrams) VALUES ('rebuild');
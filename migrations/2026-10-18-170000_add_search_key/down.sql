@@ -0,0 +1,2 @@
+DROP INDEX lecsicon_search_key;
+ALTER TABLE lecsicon DROP COLUMN search_key;
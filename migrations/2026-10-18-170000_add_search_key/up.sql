@@ -0,0 +1,63 @@
+ALTER TABLE lecsicon ADD COLUMN search_key TEXT;
+UPDATE lecsicon SET search_key = lower(word);
+UPDATE lecsicon SET search_key = replace(search_key, 'â', 'a') WHERE instr(search_key, 'â') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'à', 'a') WHERE instr(search_key, 'à') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'á', 'a') WHERE instr(search_key, 'á') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ä', 'a') WHERE instr(search_key, 'ä') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Â', 'a') WHERE instr(search_key, 'Â') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'À', 'a') WHERE instr(search_key, 'À') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Á', 'a') WHERE instr(search_key, 'Á') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ä', 'a') WHERE instr(search_key, 'Ä') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ê', 'e') WHERE instr(search_key, 'ê') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'è', 'e') WHERE instr(search_key, 'è') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'é', 'e') WHERE instr(search_key, 'é') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ë', 'e') WHERE instr(search_key, 'ë') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ê', 'e') WHERE instr(search_key, 'Ê') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'È', 'e') WHERE instr(search_key, 'È') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'É', 'e') WHERE instr(search_key, 'É') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ë', 'e') WHERE instr(search_key, 'Ë') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'î', 'i') WHERE instr(search_key, 'î') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ì', 'i') WHERE instr(search_key, 'ì') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'í', 'i') WHERE instr(search_key, 'í') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ï', 'i') WHERE instr(search_key, 'ï') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Î', 'i') WHERE instr(search_key, 'Î') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ì', 'i') WHERE instr(search_key, 'Ì') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Í', 'i') WHERE instr(search_key, 'Í') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ï', 'i') WHERE instr(search_key, 'Ï') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ô', 'o') WHERE instr(search_key, 'ô') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ò', 'o') WHERE instr(search_key, 'ò') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ó', 'o') WHERE instr(search_key, 'ó') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ö', 'o') WHERE instr(search_key, 'ö') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ô', 'o') WHERE instr(search_key, 'Ô') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ò', 'o') WHERE instr(search_key, 'Ò') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ó', 'o') WHERE instr(search_key, 'Ó') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ö', 'o') WHERE instr(search_key, 'Ö') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'û', 'u') WHERE instr(search_key, 'û') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ù', 'u') WHERE instr(search_key, 'ù') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ú', 'u') WHERE instr(search_key, 'ú') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ü', 'u') WHERE instr(search_key, 'ü') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Û', 'u') WHERE instr(search_key, 'Û') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ù', 'u') WHERE instr(search_key, 'Ù') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ú', 'u') WHERE instr(search_key, 'Ú') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ü', 'u') WHERE instr(search_key, 'Ü') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ŵ', 'w') WHERE instr(search_key, 'ŵ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ẁ', 'w') WHERE instr(search_key, 'ẁ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ẃ', 'w') WHERE instr(search_key, 'ẃ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ẅ', 'w') WHERE instr(search_key, 'ẅ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ŵ', 'w') WHERE instr(search_key, 'Ŵ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ẁ', 'w') WHERE instr(search_key, 'Ẁ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ẃ', 'w') WHERE instr(search_key, 'Ẃ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ẅ', 'w') WHERE instr(search_key, 'Ẅ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ŷ', 'y') WHERE instr(search_key, 'ŷ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ỳ', 'y') WHERE instr(search_key, 'ỳ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ý', 'y') WHERE instr(search_key, 'ý') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'ÿ', 'y') WHERE instr(search_key, 'ÿ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ŷ', 'y') WHERE instr(search_key, 'Ŷ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ỳ', 'y') WHERE instr(search_key, 'Ỳ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ý', 'y') WHERE instr(search_key, 'Ý') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, 'Ÿ', 'y') WHERE instr(search_key, 'Ÿ') > 0;
+UPDATE lecsicon SET search_key = replace(search_key, char(768), '') WHERE instr(search_key, char(768)) > 0;
+UPDATE lecsicon SET search_key = replace(search_key, char(769), '') WHERE instr(search_key, char(769)) > 0;
+UPDATE lecsicon SET search_key = replace(search_key, char(770), '') WHERE instr(search_key, char(770)) > 0;
+UPDATE lecsicon SET search_key = replace(search_key, char(776), '') WHERE instr(search_key, char(776)) > 0;
+CREATE INDEX lecsicon_search_key ON lecsicon (search_key);
@@ -1,159 +1,511 @@
-use crate::schema::lecsicon;
+#[cfg(feature = "sqlite")]
+use crate::schema::{lecsicon, lecsicon_metadata, lecsicon_rejects, lemmas};
 
+#[cfg(feature = "sqlite")]
+use diesel::deserialize::FromSqlRow;
+#[cfg(feature = "sqlite")]
+use diesel::expression::AsExpression;
+#[cfg(feature = "sqlite")]
 use diesel::prelude::*;
-use std::rc::Rc;
+#[cfg(feature = "sqlite")]
+use diesel::r2d2::{ConnectionManager, Pool};
+#[cfg(feature = "sqlite")]
+use diesel::sql_types::Text;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+#[cfg(feature = "sqlite")]
+use std::sync::{Arc, Mutex};
 
-#[derive(Clone, Debug, PartialEq)]
+// Each feature is stored in its column as an integer code, which is the
+// discriminant of its value here and is listed with the UD label in the
+// column's <column>_codes table. Codes number the values in alphabetical
+// order, so Person has One, Three, Two and Zero as 0 to 3; a feature with a
+// single value stores it as 1. Databases keep these codes, so they must
+// never change.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::TagCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Tag {
-    Adjective,
-    Adposition,
-    Adverb,
-    Conjugation,
-    Determiner,
-    Interjection,
-    Noun,
-    Number,
-    Particle,
-    Pronoun,
-    ProperNoun,
-    Verb,
+    Adjective = 0,
+    Adposition = 1,
+    Adverb = 2,
+    Conjugation = 3,
+    Determiner = 4,
+    Interjection = 5,
+    Noun = 6,
+    Number = 7,
+    Particle = 8,
+    Pronoun = 9,
+    ProperNoun = 10,
+    Verb = 11,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::AdpositionTypeCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum AdpositionType {
-    Preposition,
+    Preposition = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::DefiniteCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Definite {
-    Definite,
+    Definite = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::DegreeCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Degree {
-    Comparative,
-    Equative,
-    Positive,
-    Superlative,
+    Comparative = 0,
+    Equative = 1,
+    Positive = 2,
+    Superlative = 3,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::GenderCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Gender {
-    Feminine,
-    FeminineMasculine,
-    Masculine,
+    Feminine = 0,
+    FeminineMasculine = 1,
+    Masculine = 2,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::MoodCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Mood {
-    Imperative,
-    Indicative,
-    Subjunctive,
+    Imperative = 0,
+    Indicative = 1,
+    Subjunctive = 2,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::MutationCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Mutation {
-    Aspirate,
-    HProthesis,
-    Nasal,
-    Soft,
+    Aspirate = 0,
+    HProthesis = 1,
+    Nasal = 2,
+    Soft = 3,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::NameTypeCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NameType {
-    Geographical,
-    Person,
+    Geographical = 0,
+    Person = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::NumberCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Number {
-    Collective,
-    Plural,
-    Singular,
+    Collective = 0,
+    Plural = 1,
+    Singular = 2,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::NumberFormCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NumberForm {
-    Word,
+    Word = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::NumberTypeCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum NumberType {
-    Cardinal,
-    Ordinal,
+    Cardinal = 0,
+    Ordinal = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::PersonCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Person {
-    Zero,
-    One,
-    Two,
-    Three,
+    Zero = 3,
+    One = 0,
+    Two = 2,
+    Three = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::PolarityCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Polarity {
-    Negative,
+    Negative = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::PoliteCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Polite {
-    Formal,
-    Informal,
+    Formal = 0,
+    Informal = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::PositionCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Position {
-    Prenom,
+    Prenom = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::PossessiveCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Possessive {
-    Yes,
+    Yes = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::PronounTypeCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum PronounType {
-    Article,
-    Demonstrative,
-    Interrogative,
-    Negative,
-    Personal,
-    Relative,
-    Reflexive,
+    Article = 0,
+    Demonstrative = 1,
+    Interrogative = 2,
+    Negative = 3,
+    Personal = 4,
+    Relative = 6,
+    Reflexive = 5,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::RelativeCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Relative {
-    Rel,
+    Rel = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::StyleCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Style {
-    Archaic,
-    Colloquial,
-    Formal,
+    Archaic = 0,
+    Colloquial = 1,
+    Formal = 2,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::TenseCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Tense {
-    Future,
-    Imperfect,
-    Past,
-    Plusquamperfekt,
-    Present,
+    Future = 0,
+    Imperfect = 1,
+    Past = 2,
+    Plusquamperfekt = 3,
+    Present = 4,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = sql_types::VerbFormCode)
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum VerbForm {
-    Verbnoun,
+    Verbnoun = 1,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+// One SQL type per feature column, each stored as an INTEGER code, so that
+// a column can only be compared with or set from its own feature.
+#[cfg(feature = "sqlite")]
+pub mod sql_types {
+    use diesel::query_builder::QueryId;
+    use diesel::sql_types::SqlType;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct TagCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct AdpositionTypeCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct DefiniteCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct DegreeCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct GenderCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct MoodCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct MutationCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct NameTypeCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct NumberCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct NumberFormCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct NumberTypeCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct PersonCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct PolarityCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct PoliteCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct PositionCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct PossessiveCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct PronounTypeCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct RelativeCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct StyleCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct TenseCode;
+
+    #[derive(QueryId, SqlType)]
+    #[diesel(sqlite_type(name = "Integer"))]
+    pub struct VerbFormCode;
+}
+
+// `Feature=Value` pairs with no typed field, such as features added to UD
+// after this crate was released, kept in name order. Stored as the pairs
+// joined with |, or NULL when there are none.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(AsExpression, FromSqlRow),
+    diesel(sql_type = Text)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeatureSet(pub(crate) BTreeMap<String, String>);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(Queryable, Selectable, Insertable, AsChangeset),
+    diesel(table_name = lecsicon),
+    diesel(check_for_backend(diesel::sqlite::Sqlite)),
+    diesel(treat_none_as_default_value = false, treat_none_as_null = true)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LecsiconEntry {
     pub id: i32,
     pub word: String,
+    // Stored once, in the lemmas row that lemma_id points to, so entries
+    // must be loaded joined to lemmas.
+    #[cfg_attr(
+        feature = "sqlite",
+        diesel(
+            select_expression = lemmas::lemma.nullable(),
+            skip_insertion,
+            skip_update
+        )
+    )]
     pub lemma: Option<String>,
+    // The lemmas row for lemma and tag, once the entry is in a database.
+    pub lemma_id: Option<i32>,
     pub tag: Option<Tag>,
     pub adposition_type: Option<AdpositionType>,
     pub definite: Option<Definite>,
@@ -175,42 +527,300 @@ pub struct LecsiconEntry {
     pub style: Option<Style>,
     pub tense: Option<Tense>,
     pub verb_form: Option<VerbForm>,
+    pub other_features: FeatureSet,
+    // Kept as read so the entry can be written back out unchanged.
+    pub source_line: Option<i32>,
+    pub raw_features: Option<String>,
+    pub extra_fields: Option<String>,
 }
 
-#[derive(Queryable, Selectable, Insertable, Debug, Clone, PartialEq)]
+// An entry as inserted, with the key that folded lookups match on.
+#[cfg(feature = "sqlite")]
+#[derive(Insertable)]
 #[diesel(table_name = lecsicon)]
-#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
-pub struct SqliteLecsiconEntry {
-    pub id: i32,
-    pub word: String,
-    pub lemma: Option<String>,
-    pub tag: Option<i32>,
-    pub adposition_type: Option<bool>,
-    pub definite: Option<bool>,
-    pub degree: Option<i32>,
-    pub gender: Option<i32>,
-    pub mood: Option<i32>,
-    pub mutation: Option<i32>,
-    pub name_type: Option<i32>,
-    pub number: Option<i32>,
-    pub number_form: Option<bool>,
-    pub number_type: Option<i32>,
-    pub person: Option<i32>,
-    pub polarity: Option<bool>,
-    pub polite: Option<i32>,
-    pub position: Option<bool>,
-    pub possessive: Option<bool>,
-    pub pronoun_type: Option<i32>,
-    pub relative: Option<bool>,
-    pub style: Option<i32>,
-    pub tense: Option<i32>,
-    pub verb_form: Option<bool>,
+pub(crate) struct NewLecsiconEntry {
+    #[diesel(embed)]
+    pub entry: LecsiconEntry,
+    pub search_key: String,
+}
+
+// A handle on a lexicon database that can be shared between threads. It
+// holds a pool of connections opened in one OpenMode, and clones share the
+// pool. An in-memory database lasts as long as any clone, through the
+// connection that first opened it.
+#[cfg(feature = "sqlite")]
+#[derive(Clone)]
+pub struct Lexicon {
+    pub(crate) pool: Pool<ConnectionManager<SqliteConnection>>,
+    pub(crate) _anchor: Option<Arc<Mutex<SqliteConnection>>>,
 }
 
+#[cfg(feature = "sqlite")]
 #[derive(Clone)]
 pub struct SqliteSearcher {
     pub input: String,
     pub recent_searches: Vec<String>,
-    pub connection: Rc<SqliteConnection>,
+    pub lexicon: Lexicon,
     pub commands: Vec<String>,
+    pub hunspell_aff: std::path::PathBuf,
+    pub hunspell_dic: std::path::PathBuf,
+}
+
+// The number of migrations in migrations/, bumped with each new one.
+pub const SCHEMA_VERSION: i32 = 10;
+
+// One row per import or update, recording where the data came from.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(Queryable, Selectable),
+    diesel(table_name = lecsicon_metadata),
+    diesel(check_for_backend(diesel::sqlite::Sqlite))
+)]
+pub struct ImportMetadata {
+    pub id: i32,
+    pub source_path: String,
+    pub source_sha256: String,
+    pub rows_read: i32,
+    pub rows_total: i32,
+    pub imported_at: String,
+    pub crate_version: String,
+    pub schema_version: i32,
+    pub rows_rejected: i32,
+}
+
+#[cfg(feature = "sqlite")]
+#[derive(Insertable)]
+#[diesel(table_name = lecsicon_metadata)]
+pub(crate) struct NewImportMetadata {
+    pub source_path: String,
+    pub source_sha256: String,
+    pub rows_read: i32,
+    pub rows_total: i32,
+    pub rows_rejected: i32,
+    pub crate_version: String,
+    pub schema_version: i32,
+}
+
+// How a database file is opened. `Memory` copies the whole file into RAM
+// first, so lookups never touch the disk; changes made through that
+// connection are not written back to the file. `ReadOnly` refuses every
+// write, and `Immutable` also promises SQLite that nothing else will change
+// the file, so it skips locking; use it only for files nobody writes to,
+// such as a database shipped inside an application bundle. Neither runs
+// migrations, so the database must already have this crate's schema.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OpenMode {
+    #[default]
+    ReadWrite,
+    Memory,
+    ReadOnly,
+    Immutable,
+}
+
+// Exact lookups match the word as typed. Folded lookups also match it with
+// different case or without the accents Welsh puts on vowels, so "dwr"
+// finds "dŵr"; the closest spellings are listed first.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SearchMode {
+    #[default]
+    Exact,
+    Folded,
+}
+
+// Strict imports stop at the first line they cannot parse. Lenient imports
+// keep every line they understand and set the rest aside in lecsicon_rejects.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ImportMode {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub rows_read: usize,
+    pub rows_imported: usize,
+    pub rows_rejected: usize,
+    // Malformed pairs only; well-formed unknown pairs go to other_features.
+    pub features_rejected: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "sqlite",
+    derive(Queryable, Selectable),
+    diesel(table_name = lecsicon_rejects),
+    diesel(check_for_backend(diesel::sqlite::Sqlite))
+)]
+pub struct LecsiconReject {
+    pub id: i32,
+    pub line: i32,
+    pub raw_line: String,
+    pub reason: String,
+}
+
+// A word read back from raw SQL, for the queries Diesel's DSL cannot
+// express, such as FTS5 MATCH.
+#[cfg(feature = "sqlite")]
+#[derive(QueryableByName)]
+pub(crate) struct WordRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub(crate) word: String,
+}
+
+#[cfg(feature = "sqlite")]
+#[derive(Insertable)]
+#[diesel(table_name = lecsicon_rejects)]
+pub(crate) struct NewLecsiconReject {
+    pub line: i32,
+    pub raw_line: String,
+    pub reason: String,
+}
+
+// Restricts an export to matching entries; fields left as None match everything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExportFilter {
+    pub tag: Option<Tag>,
+    pub lemma: Option<String>,
+    pub word_prefix: Option<String>,
+}
+
+// A row of the lemmas table, with the number of entries inflected from it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lemma {
+    pub id: i32,
+    pub lemma: String,
+    pub tag: Option<Tag>,
+    pub forms: usize,
+}
+
+// The outcome of a UniMorph export: how many triples were written, and how
+// often each UD feature with no UniMorph equivalent was left out.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UnimorphReport {
+    pub entries_written: usize,
+    pub unmapped: BTreeMap<String, usize>,
+}
+
+// The outcome of a Hunspell export. Forms reachable through a stem's
+// mutation flags are not written out again; words containing whitespace
+// cannot be stored in a .dic file and are skipped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HunspellReport {
+    pub stems_written: usize,
+    pub flagged_stems: usize,
+    pub forms_covered: usize,
+    pub words_skipped: usize,
+}
+
+// Differences between a lecsicon CSV and a database, each list sorted by word.
+// Changed analyses keep their existing id and are given as (old, new) pairs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LecsiconChanges {
+    pub added: Vec<LecsiconEntry>,
+    pub removed: Vec<LecsiconEntry>,
+    pub changed: Vec<(LecsiconEntry, LecsiconEntry)>,
+    pub summary: ImportSummary,
+}
+
+#[cfg(feature = "sqlite")]
+pub(crate) struct SqliteChanges {
+    pub added: Vec<LecsiconEntry>,
+    pub removed: Vec<LecsiconEntry>,
+    pub changed: Vec<(LecsiconEntry, LecsiconEntry)>,
+    // Unchanged analyses that sit on a different line of the new file.
+    pub moved: Vec<LecsiconEntry>,
+}
+
+// Yields one entry per line of a lecsicon CSV, without holding the whole file in memory.
+pub struct LecsiconReader<R: std::io::Read> {
+    pub(crate) records: csv::StringRecordsIntoIter<R>,
+    pub(crate) next_ids: HashMap<String, i32>,
+    pub(crate) pending: VecDeque<Result<LecsiconEntry, LecsiconError>>,
+}
+
+// A lexicon compiled by `write_sqlite_db_as_snapshot`, read in place from
+// a byte slice such as a memory-mapped file. Words map through an FST to
+// their packed analyses, and lemmas to the words inflected from them.
+#[cfg(feature = "snapshot")]
+pub struct LecsiconSnapshot<'a> {
+    pub(crate) words: fst::Map<&'a [u8]>,
+    pub(crate) lemmas: fst::Map<&'a [u8]>,
+    pub(crate) analyses: &'a [u8],
+    pub(crate) postings: &'a [u8],
+    pub(crate) string_offsets: &'a [u8],
+    pub(crate) strings: &'a [u8],
+}
+
+// Line and column numbers are 1-based, as in a text editor.
+#[derive(Debug)]
+pub enum LecsiconError {
+    // A lecsicon CSV file could not be opened, read or written.
+    Io(std::io::Error),
+    // The CSV reader could not parse a record.
+    Csv(csv::Error),
+    // A record ended before a required column.
+    MissingField {
+        line: u64,
+        column: usize,
+        record: String,
+    },
+    // The part-of-speech column held an unknown UPOS tag.
+    UnrecognisedTag {
+        line: u64,
+        column: usize,
+        raw: String,
+        record: String,
+    },
+    // The feature column held a malformed `Feature=Value` pair. The reader
+    // yields this before the rest of the entry, which is still usable.
+    UnrecognisedFeature {
+        line: u64,
+        column: usize,
+        raw: String,
+        record: String,
+    },
+    // No database file exists at the given path.
+    MissingDatabase(std::path::PathBuf),
+    // A connection to the database could not be established.
+    #[cfg(feature = "sqlite")]
+    Connection(diesel::ConnectionError),
+    // A database file could not be copied into memory.
+    Backup(String),
+    // A write was attempted through a read-only or immutable connection.
+    ReadOnly,
+    // `":memory:"` was opened read-only or immutable; it would always be
+    // empty.
+    InvalidOpenMode(OpenMode),
+    // A database opened read-only lacks a migration this crate needs.
+    OutdatedSchema(String),
+    // No connection could be taken from a `Lexicon`'s pool.
+    #[cfg(feature = "sqlite")]
+    Pool(diesel::r2d2::PoolError),
+    // The migrations bundled with the crate could not be applied.
+    Migration(Box<dyn std::error::Error + Send + Sync>),
+    // The database has a migration this crate doesn't know, so it was
+    // written by a newer version.
+    UnsupportedSchema(String),
+    // A query or insert failed.
+    #[cfg(feature = "sqlite")]
+    Database(diesel::result::Error),
+    // Entries could not be written as JSON.
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+    // A snapshot FST could not be built or read.
+    #[cfg(feature = "snapshot")]
+    Fst(fst::Error),
+    // A snapshot was truncated, or is not a lecsicon snapshot at all.
+    #[cfg(feature = "snapshot")]
+    InvalidSnapshot(&'static str),
+    // The query ran but matched no entries.
+    NotFound(String),
+    // A database row held an integer code with no matching feature value.
+    UndecodableRow {
+        column: &'static str,
+        value: i32,
+    },
 }
@@ -1,14 +1,49 @@
 use crate::definitions::*;
 
+#[cfg(feature = "sqlite")]
+use diesel::connection::{DefaultLoadingMode, SimpleConnection};
+#[cfg(feature = "sqlite")]
+use diesel::deserialize::{self, FromSql};
+#[cfg(feature = "sqlite")]
+use diesel::migration::{MigrationSource, MigrationVersion};
+#[cfg(feature = "sqlite")]
 use diesel::prelude::*;
+#[cfg(feature = "sqlite")]
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool, PooledConnection};
+#[cfg(feature = "sqlite")]
+use diesel::serialize::{self, IsNull, Output, ToSql};
+#[cfg(feature = "sqlite")]
+use diesel::sql_types::{Integer, Nullable, Text};
+#[cfg(feature = "sqlite")]
+use diesel::sqlite::{Sqlite, SqliteValue};
+#[cfg(feature = "sqlite")]
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+#[cfg(feature = "snapshot")]
+use fst::{Automaton, IntoStreamer, Streamer};
+#[cfg(feature = "sqlite")]
 use hunspell_rs::Hunspell;
+#[cfg(feature = "sqlite")]
 use inquire::autocompletion::{Autocomplete, Replacement};
+#[cfg(feature = "sqlite")]
 use inquire::CustomUserError;
-use std::collections::VecDeque;
+#[cfg(feature = "sqlite")]
+use libsqlite3_sys as ffi;
+#[cfg(feature = "sqlite")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "sqlite")]
+use std::collections::BTreeMap;
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
+#[cfg(feature = "sqlite")]
+use std::ffi::{c_int, CStr, CString};
+use std::fmt;
 use std::fs::File;
+use std::io::{Read, Write};
 use std::path::Path;
-use std::rc::Rc;
+#[cfg(feature = "sqlite")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "sqlite")]
+use std::sync::{Arc, Mutex};
 
 impl Default for LecsiconEntry {
     fn default() -> LecsiconEntry {
@@ -16,6 +51,7 @@ impl Default for LecsiconEntry {
             id: -99,
             word: "ERROR".to_string(),
             lemma: None,
+            lemma_id: None,
             tag: None,
             adposition_type: None,
             definite: None,
@@ -37,708 +73,2597 @@ impl Default for LecsiconEntry {
             style: None,
             tense: None,
             verb_form: None,
+            other_features: FeatureSet::default(),
+            source_line: None,
+            raw_features: None,
+            extra_fields: None,
         }
     }
 }
 
-pub fn load_text_lecsicon(filename: &Path) -> Option<Vec<LecsiconEntry>> {
-    let lecsicon_file = File::open(filename);
-    match lecsicon_file {
-        Ok(file) => {
-            let mut csv_reader = csv::ReaderBuilder::new()
+impl FeatureSet {
+    // Splits a `Feature=Value` pair if it is well formed: a capitalised
+    //  name, optionally layered as in `Number[psor]`, and a value of
+    //  letters and digits, or several joined by commas.
+    pub fn split_pair(tag_pair: &str) -> Option<(&str, &str)> {
+        let (name, value) = tag_pair.split_once('=')?;
+        let name_ok = name.starts_with(|c: char| c.is_ascii_uppercase())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '[' || c == ']');
+        let value_ok = value
+            .split(',')
+            .all(|v| !v.is_empty() && v.chars().all(|c| c.is_ascii_alphanumeric()));
+        (name_ok && value_ok).then_some((name, value))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, name: &str, value: &str) -> Option<String> {
+        self.0.insert(name.to_string(), value.to_string())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.0.remove(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // Reads the `|`-separated form written by Display, skipping anything
+    //  that is not a well-formed pair.
+    pub fn parse(text: &str) -> FeatureSet {
+        let mut features = FeatureSet::default();
+        for (name, value) in text.split('|').filter_map(FeatureSet::split_pair) {
+            features.insert(name, value);
+        }
+        features
+    }
+}
+
+impl fmt::Display for FeatureSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairs: Vec<String> = self
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        write!(f, "{}", pairs.join("|"))
+    }
+}
+
+impl LecsiconEntry {
+    // Sets the field for one `Feature=Value` pair from the lecsicon feature
+    //  column, returning false if the pair is not recognised.
+    pub(crate) fn apply_ud_feature(&mut self, tag_pair: &str) -> bool {
+        match tag_pair {
+            "AdpType=Prep" => self.adposition_type = Some(AdpositionType::Preposition),
+            "Definite=Def" => self.definite = Some(Definite::Definite),
+            "Degree=Cmp" => self.degree = Some(Degree::Comparative),
+            "Degree=Equ" => self.degree = Some(Degree::Equative),
+            "Degree=Pos" => self.degree = Some(Degree::Positive),
+            "Degree=Sup" => self.degree = Some(Degree::Superlative),
+            "Gender=Fem" => self.gender = Some(Gender::Feminine),
+            "Gender=Fem,Masc" => self.gender = Some(Gender::FeminineMasculine),
+            "Gender=Masc" => self.gender = Some(Gender::Masculine),
+            "Mood=Imp" => self.mood = Some(Mood::Imperative),
+            "Mood=Ind" => self.mood = Some(Mood::Indicative),
+            "Mood=Sub" => self.mood = Some(Mood::Subjunctive),
+            "Mutation=AM" => {
+                self.mutation = Some(Mutation::Aspirate);
+            }
+            "Mutation=HM" => {
+                self.mutation = Some(Mutation::HProthesis);
+            }
+            "Mutation=NM" => {
+                self.mutation = Some(Mutation::Nasal);
+            }
+            "Mutation=SM" => {
+                self.mutation = Some(Mutation::Soft);
+            }
+            "NameType=Geo" => {
+                self.name_type = Some(NameType::Geographical);
+            }
+            "NameType=Prs" => {
+                self.name_type = Some(NameType::Person);
+            }
+            "Number=Coll" => {
+                self.number = Some(Number::Collective);
+            }
+            "Number=Plur" => {
+                self.number = Some(Number::Plural);
+            }
+            "Number=Sing" => {
+                self.number = Some(Number::Singular);
+            }
+            "Numform=Word" => {
+                self.number_form = Some(NumberForm::Word);
+            }
+            "Numtype=Card" => {
+                self.number_type = Some(NumberType::Cardinal);
+            }
+            "Numtype=Ord" => {
+                self.number_type = Some(NumberType::Ordinal);
+            }
+            "Person=0" => self.person = Some(Person::Zero),
+            "Person=1" => self.person = Some(Person::One),
+            "Person=2" => self.person = Some(Person::Two),
+            "Person=3" => self.person = Some(Person::Three),
+            "Polarity=Neg" => self.polarity = Some(Polarity::Negative),
+            "Polite=Form" => self.polite = Some(Polite::Formal),
+            "Polite=Inf" => self.polite = Some(Polite::Informal),
+            "Position=Prenom" => self.position = Some(Position::Prenom),
+            "Poss=Yes" => self.possessive = Some(Possessive::Yes),
+            "PronType=Art" => self.pronoun_type = Some(PronounType::Article),
+            "PronType=Dem" => self.pronoun_type = Some(PronounType::Demonstrative),
+            "PronType=Int" => self.pronoun_type = Some(PronounType::Interrogative),
+            "PronType=Neg" => self.pronoun_type = Some(PronounType::Negative),
+            "PronType=Prs" => self.pronoun_type = Some(PronounType::Personal),
+            "PronType=Rel" => self.pronoun_type = Some(PronounType::Relative),
+            "PronType=Tot" => self.pronoun_type = Some(PronounType::Reflexive),
+            "Relative=Rel" => self.relative = Some(Relative::Rel),
+            "Style=Arch" => self.style = Some(Style::Archaic),
+            "Style=Coll" => self.style = Some(Style::Colloquial),
+            "Style=Form" => self.style = Some(Style::Formal),
+            "Tense=Fut" => self.tense = Some(Tense::Future),
+            "Tense=Imp" => self.tense = Some(Tense::Imperfect),
+            "Tense=Past" => self.tense = Some(Tense::Past),
+            "Tense=Pqp" => self.tense = Some(Tense::Plusquamperfekt),
+            "Tense=Pres" => self.tense = Some(Tense::Present),
+            "VerbForm=Vnoun" => self.verb_form = Some(VerbForm::Verbnoun),
+            "" => {}
+            " " => {}
+            _ => return false,
+        }
+        true
+    }
+
+    // Like `apply_ud_feature`, but keeps any other well-formed pair in
+    //  `other_features`, so only malformed pairs are refused.
+    pub(crate) fn add_feature(&mut self, tag_pair: &str) -> bool {
+        if self.apply_ud_feature(tag_pair) {
+            return true;
+        }
+        match FeatureSet::split_pair(tag_pair) {
+            Some((name, value)) => {
+                self.other_features.insert(name, value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Every feature of the entry, typed or not, in UD's alphabetical order.
+    pub fn feature_pairs(&self) -> Vec<String> {
+        let mut pairs: Vec<String> = self.ud_features().iter().map(|f| f.to_string()).collect();
+        pairs.extend(
+            self.other_features
+                .iter()
+                .map(|(name, value)| format!("{name}={value}")),
+        );
+        pairs.sort_by_key(|pair| pair.split('=').next().unwrap_or_default().to_lowercase());
+        pairs
+    }
+
+    // The value of a feature by its UD name, whether or not it has a
+    //  typed field.
+    pub fn feature(&self, name: &str) -> Option<&str> {
+        self.ud_features()
+            .into_iter()
+            .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+            .or_else(|| self.other_features.get(name))
+    }
+
+    // The entry's features as `Feature=Value` pairs, in lecsicon order.
+    pub fn ud_features(&self) -> Vec<&'static str> {
+        let mut features = vec![];
+        if let Some(AdpositionType::Preposition) = self.adposition_type {
+            features.push("AdpType=Prep");
+        }
+        if let Some(Definite::Definite) = self.definite {
+            features.push("Definite=Def");
+        }
+        if let Some(degree) = &self.degree {
+            features.push(match degree {
+                Degree::Comparative => "Degree=Cmp",
+                Degree::Equative => "Degree=Equ",
+                Degree::Positive => "Degree=Pos",
+                Degree::Superlative => "Degree=Sup",
+            });
+        }
+        if let Some(gender) = &self.gender {
+            features.push(match gender {
+                Gender::Feminine => "Gender=Fem",
+                Gender::FeminineMasculine => "Gender=Fem,Masc",
+                Gender::Masculine => "Gender=Masc",
+            });
+        }
+        if let Some(mood) = &self.mood {
+            features.push(match mood {
+                Mood::Imperative => "Mood=Imp",
+                Mood::Indicative => "Mood=Ind",
+                Mood::Subjunctive => "Mood=Sub",
+            });
+        }
+        if let Some(mutation) = &self.mutation {
+            features.push(match mutation {
+                Mutation::Aspirate => "Mutation=AM",
+                Mutation::HProthesis => "Mutation=HM",
+                Mutation::Nasal => "Mutation=NM",
+                Mutation::Soft => "Mutation=SM",
+            });
+        }
+        if let Some(name_type) = &self.name_type {
+            features.push(match name_type {
+                NameType::Geographical => "NameType=Geo",
+                NameType::Person => "NameType=Prs",
+            });
+        }
+        if let Some(number) = &self.number {
+            features.push(match number {
+                Number::Collective => "Number=Coll",
+                Number::Plural => "Number=Plur",
+                Number::Singular => "Number=Sing",
+            });
+        }
+        if let Some(NumberForm::Word) = self.number_form {
+            features.push("Numform=Word");
+        }
+        if let Some(number_type) = &self.number_type {
+            features.push(match number_type {
+                NumberType::Cardinal => "Numtype=Card",
+                NumberType::Ordinal => "Numtype=Ord",
+            });
+        }
+        if let Some(person) = &self.person {
+            features.push(match person {
+                Person::Zero => "Person=0",
+                Person::One => "Person=1",
+                Person::Two => "Person=2",
+                Person::Three => "Person=3",
+            });
+        }
+        if let Some(Polarity::Negative) = self.polarity {
+            features.push("Polarity=Neg");
+        }
+        if let Some(polite) = &self.polite {
+            features.push(match polite {
+                Polite::Formal => "Polite=Form",
+                Polite::Informal => "Polite=Inf",
+            });
+        }
+        if let Some(Position::Prenom) = self.position {
+            features.push("Position=Prenom");
+        }
+        if let Some(Possessive::Yes) = self.possessive {
+            features.push("Poss=Yes");
+        }
+        if let Some(pronoun_type) = &self.pronoun_type {
+            features.push(match pronoun_type {
+                PronounType::Article => "PronType=Art",
+                PronounType::Demonstrative => "PronType=Dem",
+                PronounType::Interrogative => "PronType=Int",
+                PronounType::Negative => "PronType=Neg",
+                PronounType::Personal => "PronType=Prs",
+                PronounType::Relative => "PronType=Rel",
+                PronounType::Reflexive => "PronType=Tot",
+            });
+        }
+        if let Some(Relative::Rel) = self.relative {
+            features.push("Relative=Rel");
+        }
+        if let Some(style) = &self.style {
+            features.push(match style {
+                Style::Archaic => "Style=Arch",
+                Style::Colloquial => "Style=Coll",
+                Style::Formal => "Style=Form",
+            });
+        }
+        if let Some(tense) = &self.tense {
+            features.push(match tense {
+                Tense::Future => "Tense=Fut",
+                Tense::Imperfect => "Tense=Imp",
+                Tense::Past => "Tense=Past",
+                Tense::Plusquamperfekt => "Tense=Pqp",
+                Tense::Present => "Tense=Pres",
+            });
+        }
+        if let Some(VerbForm::Verbnoun) = self.verb_form {
+            features.push("VerbForm=Vnoun");
+        }
+        features
+    }
+
+    // The entry's UniMorph features, followed by the UD features that have
+    //  no UniMorph equivalent.
+    pub fn unimorph_features(&self) -> (Vec<&'static str>, Vec<String>) {
+        let mut features = vec![];
+        let mut unmapped: Vec<String> = vec![];
+        match self.tag {
+            Some(Tag::Adjective) => features.push("ADJ"),
+            Some(Tag::Adposition) => features.push("ADP"),
+            Some(Tag::Adverb) => features.push("ADV"),
+            Some(Tag::Conjugation) => features.push("CONJ"),
+            Some(Tag::Determiner) => features.push("DET"),
+            Some(Tag::Interjection) => features.push("INTJ"),
+            Some(Tag::Noun) => features.push("N"),
+            Some(Tag::Number) => features.push("NUM"),
+            Some(Tag::Particle) => features.push("PART"),
+            Some(Tag::Pronoun) => features.push("PRO"),
+            Some(Tag::ProperNoun) => features.push("PROPN"),
+            Some(Tag::Verb) if self.verb_form.is_some() => features.push("V.MSDR"),
+            Some(Tag::Verb) => features.push("V"),
+            None => {}
+        }
+        for ud_feature in self.ud_features() {
+            match ud_feature {
+                "Definite=Def" => features.push("DEF"),
+                "Degree=Cmp" => features.push("CMPR"),
+                "Degree=Equ" => features.push("EQT"),
+                "Degree=Sup" => features.push("SPRL"),
+                "Gender=Fem" => features.push("FEM"),
+                "Gender=Masc" => features.push("MASC"),
+                "Mood=Imp" => features.push("IMP"),
+                "Mood=Ind" => features.push("IND"),
+                "Mood=Sub" => features.push("SBJV"),
+                "Number=Plur" => features.push("PL"),
+                "Number=Sing" => features.push("SG"),
+                "Person=0" => features.push("0"),
+                "Person=1" => features.push("1"),
+                "Person=2" => features.push("2"),
+                "Person=3" => features.push("3"),
+                "Polarity=Neg" => features.push("NEG"),
+                "Polite=Form" => features.push("FORM"),
+                "Polite=Inf" => features.push("INFM"),
+                "Tense=Fut" => features.push("FUT"),
+                "Tense=Imp" => features.extend(["PST", "IPFV"]),
+                "Tense=Past" => features.push("PST"),
+                "Tense=Pqp" => features.extend(["PST", "PRF"]),
+                "Tense=Pres" => features.push("PRS"),
+                // carried by the V.MSDR part of speech
+                "VerbForm=Vnoun" => {}
+                _ => unmapped.push(ud_feature.to_string()),
+            }
+        }
+        unmapped.extend(
+            self.other_features
+                .iter()
+                .map(|(name, value)| format!("{name}={value}")),
+        );
+        // pairs a lenient import could not read are still in the raw text
+        if let Some(raw_features) = &self.raw_features {
+            let mut scratch = LecsiconEntry {
+                ..Default::default()
+            };
+            unmapped.extend(
+                raw_features
+                    .split('|')
+                    .filter(|tag_pair| !scratch.add_feature(tag_pair))
+                    .map(|tag_pair| tag_pair.to_string()),
+            );
+        }
+        (features, unmapped)
+    }
+
+    // The feature column to export: the text as imported while it still
+    //  describes the entry, otherwise one rebuilt from its features.
+    fn csv_features(&self) -> Option<String> {
+        let features = self.feature_pairs();
+        if let Some(raw_features) = &self.raw_features {
+            let mut imported = LecsiconEntry {
+                ..Default::default()
+            };
+            for tag_pair in raw_features.split('|') {
+                imported.add_feature(tag_pair);
+            }
+            if imported.feature_pairs() == features {
+                return Some(raw_features.clone());
+            }
+        }
+        if features.is_empty() {
+            None
+        } else {
+            Some(features.join("|"))
+        }
+    }
+}
+
+impl<R: Read> LecsiconReader<R> {
+    pub fn new(reader: R) -> LecsiconReader<R> {
+        LecsiconReader {
+            records: csv::ReaderBuilder::new()
                 .has_headers(false)
                 .delimiter(b'\t')
                 .flexible(true)
-                .from_reader(file);
-            let mut all_entries: Vec<LecsiconEntry> = vec![];
-
-            let mut previous_entries: VecDeque<(String, i32)> = VecDeque::new();
-            for result in csv_reader.records() {
-                let mut new_lecsicon_entry = LecsiconEntry {
-                    ..Default::default()
-                };
-                match result {
-                    Ok(record) => {
-                        new_lecsicon_entry.id = 101;
-                        new_lecsicon_entry.word = record.get(0)?.to_string();
-                        new_lecsicon_entry.lemma = Some(record.get(1)?.to_string());
-                        new_lecsicon_entry.tag = match record.get(2)? {
-                            "ADJ" => Some(Tag::Adjective),
-                            "ADP" => Some(Tag::Adposition),
-                            "ADV" => Some(Tag::Adverb),
-                            "CONJ" => Some(Tag::Conjugation),
-                            "DET" => Some(Tag::Determiner),
-                            "INTJ" => Some(Tag::Interjection),
-                            "NOUN" => Some(Tag::Noun),
-                            "NUM" => Some(Tag::Number),
-                            "PART" => Some(Tag::Particle),
-                            "PRON" => Some(Tag::Pronoun),
-                            "PROPN" => Some(Tag::ProperNoun),
-                            "VERB" => Some(Tag::Verb),
-                            _ => {
-                                println!("{}", record.get(2)?);
-                                panic!("Tag not recognised");
-                            }
-                        };
+                .quoting(false)
+                .from_reader(reader)
+                .into_records(),
+            next_ids: HashMap::new(),
+            pending: VecDeque::new(),
+        }
+    }
 
-                        for (word, id) in previous_entries.iter() {
-                            if *word == new_lecsicon_entry.word && *id >= new_lecsicon_entry.id {
-                                new_lecsicon_entry.id = id + 1;
-                            }
-                        }
-                        previous_entries
-                            .push_front((new_lecsicon_entry.word.clone(), new_lecsicon_entry.id));
-                        previous_entries.truncate(20);
-
-                        if record.len() > 3 {
-                            let raw_details = record.get(3)?;
-                            let details = raw_details.split('|');
-                            for tag_pair in details {
-                                match tag_pair {
-                                    "AdpType=Prep" => {
-                                        new_lecsicon_entry.adposition_type =
-                                            Some(AdpositionType::Preposition)
-                                    }
-                                    "Definite=Def" => {
-                                        new_lecsicon_entry.definite = Some(Definite::Definite)
-                                    }
-                                    "Degree=Cmp" => {
-                                        new_lecsicon_entry.degree = Some(Degree::Comparative)
-                                    }
-                                    "Degree=Equ" => {
-                                        new_lecsicon_entry.degree = Some(Degree::Equative)
-                                    }
-                                    "Degree=Pos" => {
-                                        new_lecsicon_entry.degree = Some(Degree::Positive)
-                                    }
-                                    "Degree=Sup" => {
-                                        new_lecsicon_entry.degree = Some(Degree::Superlative)
-                                    }
-                                    "Gender=Fem" => {
-                                        new_lecsicon_entry.gender = Some(Gender::Feminine)
-                                    }
-                                    "Gender=Fem,Masc" => {
-                                        new_lecsicon_entry.gender = Some(Gender::FeminineMasculine)
-                                    }
-                                    "Gender=Masc" => {
-                                        new_lecsicon_entry.gender = Some(Gender::Masculine)
-                                    }
-                                    "Mood=Imp" => new_lecsicon_entry.mood = Some(Mood::Imperative),
-                                    "Mood=Ind" => new_lecsicon_entry.mood = Some(Mood::Indicative),
-                                    "Mood=Sub" => new_lecsicon_entry.mood = Some(Mood::Subjunctive),
-                                    "Mutation=AM" => {
-                                        new_lecsicon_entry.mutation = Some(Mutation::Aspirate);
-                                    }
-                                    "Mutation=HM" => {
-                                        new_lecsicon_entry.mutation = Some(Mutation::HProthesis);
-                                    }
-                                    "Mutation=NM" => {
-                                        new_lecsicon_entry.mutation = Some(Mutation::Nasal);
-                                    }
-                                    "Mutation=SM" => {
-                                        new_lecsicon_entry.mutation = Some(Mutation::Soft);
-                                    }
-                                    "NameType=Geo" => {
-                                        new_lecsicon_entry.name_type = Some(NameType::Geographical);
-                                    }
-                                    "NameType=Prs" => {
-                                        new_lecsicon_entry.name_type = Some(NameType::Person);
-                                    }
-                                    "Number=Coll" => {
-                                        new_lecsicon_entry.number = Some(Number::Collective);
-                                    }
-                                    "Number=Plur" => {
-                                        new_lecsicon_entry.number = Some(Number::Plural);
-                                    }
-                                    "Number=Sing" => {
-                                        new_lecsicon_entry.number = Some(Number::Singular);
-                                    }
-                                    "Numform=Word" => {
-                                        new_lecsicon_entry.number_form = Some(NumberForm::Word);
-                                    }
-                                    "Numtype=Card" => {
-                                        new_lecsicon_entry.number_type = Some(NumberType::Cardinal);
-                                    }
-                                    "Numtype=Ord" => {
-                                        new_lecsicon_entry.number_type = Some(NumberType::Ordinal);
-                                    }
-                                    "Person=0" => new_lecsicon_entry.person = Some(Person::Zero),
-                                    "Person=1" => new_lecsicon_entry.person = Some(Person::One),
-                                    "Person=2" => new_lecsicon_entry.person = Some(Person::Two),
-                                    "Person=3" => new_lecsicon_entry.person = Some(Person::Three),
-                                    "Polarity=Neg" => {
-                                        new_lecsicon_entry.polarity = Some(Polarity::Negative)
-                                    }
-                                    "Polite=Form" => {
-                                        new_lecsicon_entry.polite = Some(Polite::Formal)
-                                    }
-                                    "Polite=Inf" => {
-                                        new_lecsicon_entry.polite = Some(Polite::Informal)
-                                    }
-                                    "Position=Prenom" => {
-                                        new_lecsicon_entry.position = Some(Position::Prenom)
-                                    }
-                                    "Poss=Yes" => {
-                                        new_lecsicon_entry.possessive = Some(Possessive::Yes)
-                                    }
-                                    "PronType=Art" => {
-                                        new_lecsicon_entry.pronoun_type = Some(PronounType::Article)
-                                    }
-                                    "PronType=Dem" => {
-                                        new_lecsicon_entry.pronoun_type =
-                                            Some(PronounType::Demonstrative)
-                                    }
-                                    "PronType=Int" => {
-                                        new_lecsicon_entry.pronoun_type =
-                                            Some(PronounType::Interrogative)
-                                    }
-                                    "PronType=Neg" => {
-                                        new_lecsicon_entry.pronoun_type =
-                                            Some(PronounType::Negative)
-                                    }
-                                    "PronType=Prs" => {
-                                        new_lecsicon_entry.pronoun_type =
-                                            Some(PronounType::Personal)
-                                    }
-                                    "PronType=Rel" => {
-                                        new_lecsicon_entry.pronoun_type =
-                                            Some(PronounType::Relative)
-                                    }
-                                    "PronType=Tot" => {
-                                        new_lecsicon_entry.pronoun_type =
-                                            Some(PronounType::Reflexive)
-                                    }
-                                    "Relative=Rel" => {
-                                        new_lecsicon_entry.relative = Some(Relative::Rel)
-                                    }
-                                    "Style=Arch" => new_lecsicon_entry.style = Some(Style::Archaic),
-                                    "Style=Coll" => {
-                                        new_lecsicon_entry.style = Some(Style::Colloquial)
-                                    }
-                                    "Style=Form" => new_lecsicon_entry.style = Some(Style::Formal),
-                                    "Tense=Fut" => new_lecsicon_entry.tense = Some(Tense::Future),
-                                    "Tense=Imp" => {
-                                        new_lecsicon_entry.tense = Some(Tense::Imperfect)
-                                    }
-                                    "Tense=Past" => new_lecsicon_entry.tense = Some(Tense::Past),
-                                    "Tense=Pqp" => {
-                                        new_lecsicon_entry.tense = Some(Tense::Plusquamperfekt)
-                                    }
-                                    "Tense=Pres" => new_lecsicon_entry.tense = Some(Tense::Present),
-                                    "VerbForm=Vnoun" => {
-                                        new_lecsicon_entry.verb_form = Some(VerbForm::Verbnoun)
-                                    }
-                                    "" => {}
-                                    " " => {}
-                                    _ => panic!("Unrecognised tag pair: <{tag_pair}>"),
-                                }
-                            }
-                        }
-                        all_entries.push(new_lecsicon_entry);
-                    }
-                    Err(res) => {
-                        println!("problem with the following entry:");
-                        dbg!(new_lecsicon_entry);
-                        dbg!(res);
-                    }
+    fn parse_record(&mut self, record: csv::StringRecord) -> Result<LecsiconEntry, LecsiconError> {
+        let line = record.position().map_or(0, |position| position.line());
+        let raw_record = || record.iter().collect::<Vec<&str>>().join("\t");
+        let field = |column: usize| {
+            record
+                .get(column)
+                .ok_or_else(|| LecsiconError::MissingField {
+                    line,
+                    column: column + 1,
+                    record: raw_record(),
+                })
+        };
+
+        let mut new_lecsicon_entry = LecsiconEntry {
+            ..Default::default()
+        };
+        new_lecsicon_entry.word = field(0)?.to_string();
+        new_lecsicon_entry.lemma = Some(field(1)?.to_string());
+        new_lecsicon_entry.tag = match Tag::from_upos(field(2)?) {
+            Some(tag) => Some(tag),
+            None => {
+                return Err(LecsiconError::UnrecognisedTag {
+                    line,
+                    column: 3,
+                    raw: field(2)?.to_string(),
+                    record: raw_record(),
+                })
+            }
+        };
+
+        // homographs are numbered from 101 in file order, so an
+        //  unchanged file always gives each analysis the same id
+        let next_id = self
+            .next_ids
+            .entry(new_lecsicon_entry.word.clone())
+            .or_insert(101);
+        new_lecsicon_entry.id = *next_id;
+        *next_id += 1;
+
+        if record.len() > 3 {
+            let raw_details = field(3)?;
+            let details = raw_details.split('|');
+            for tag_pair in details {
+                if !new_lecsicon_entry.add_feature(tag_pair) {
+                    // the rest of the entry is still usable, so report the
+                    //  feature ahead of it and let the caller decide
+                    self.pending
+                        .push_back(Err(LecsiconError::UnrecognisedFeature {
+                            line,
+                            column: 4,
+                            raw: tag_pair.to_string(),
+                            record: raw_record(),
+                        }));
                 }
             }
-            Some(all_entries)
         }
-        Err(_) => {
-            println!("loadtextlecsicon failed");
-            None
+        new_lecsicon_entry.source_line = Some(line as i32);
+        new_lecsicon_entry.raw_features = record.get(3).map(|raw| raw.to_string());
+        if record.len() > 4 {
+            new_lecsicon_entry.extra_fields =
+                Some(record.iter().skip(4).collect::<Vec<&str>>().join("\t"));
         }
+        Ok(new_lecsicon_entry)
     }
 }
-pub fn convert_sqlite_to_text(sqlite_entry: SqliteLecsiconEntry) -> LecsiconEntry {
-    LecsiconEntry {
-        id: sqlite_entry.id,
-        word: sqlite_entry.word,
-        lemma: sqlite_entry.lemma,
-        tag: match sqlite_entry.tag {
-            Some(tag) => match tag {
-                0 => Some(Tag::Adjective),
-                1 => Some(Tag::Adposition),
-                2 => Some(Tag::Adverb),
-                3 => Some(Tag::Conjugation),
-                4 => Some(Tag::Determiner),
-                5 => Some(Tag::Interjection),
-                6 => Some(Tag::Noun),
-                7 => Some(Tag::Number),
-                8 => Some(Tag::Particle),
-                9 => Some(Tag::Pronoun),
-                10 => Some(Tag::ProperNoun),
-                11 => Some(Tag::Verb),
-                _ => {
-                    dbg!(tag);
-                    panic!("tag not recognised");
-                }
-            },
-            None => None,
-        },
-        adposition_type: sqlite_entry
-            .adposition_type
-            .map(|_true_var| AdpositionType::Preposition),
-        definite: sqlite_entry.definite.map(|_true_var| Definite::Definite),
-        degree: match sqlite_entry.degree {
-            Some(degree) => match degree {
-                0 => Some(Degree::Comparative),
-                1 => Some(Degree::Equative),
-                2 => Some(Degree::Positive),
-                3 => Some(Degree::Superlative),
-                _ => {
-                    dbg!(degree);
-                    panic!("degree not recognised");
-                }
-            },
-            None => None,
-        },
-        gender: match sqlite_entry.gender {
-            Some(gender) => match gender {
-                0 => Some(Gender::Feminine),
-                1 => Some(Gender::FeminineMasculine),
-                2 => Some(Gender::Masculine),
-                _ => {
-                    dbg!(gender);
-                    panic!("gender not recognised");
-                }
-            },
-            None => None,
-        },
-        mood: match sqlite_entry.mood {
-            Some(mood) => match mood {
-                0 => Some(Mood::Imperative),
-                1 => Some(Mood::Indicative),
-                2 => Some(Mood::Subjunctive),
-                _ => {
-                    dbg!(mood);
-                    panic!("mood not recognised");
-                }
-            },
-            None => None,
-        },
-        mutation: match sqlite_entry.mutation {
-            Some(mutation) => match mutation {
-                0 => Some(Mutation::Aspirate),
-                1 => Some(Mutation::HProthesis),
-                2 => Some(Mutation::Nasal),
-                3 => Some(Mutation::Soft),
-                _ => {
-                    dbg!(mutation);
-                    panic!("mutation not recognised");
-                }
-            },
-            None => None,
-        },
-        name_type: match sqlite_entry.name_type {
-            Some(name_type) => match name_type {
-                0 => Some(NameType::Geographical),
-                1 => Some(NameType::Person),
-                _ => {
-                    dbg!(name_type);
-                    panic!("name_type not recognised");
-                }
-            },
-            None => None,
-        },
-        number: match sqlite_entry.number {
-            Some(number) => match number {
-                0 => Some(Number::Collective),
-                1 => Some(Number::Plural),
-                2 => Some(Number::Singular),
-                _ => {
-                    dbg!(number);
-                    panic!("number not recognised");
-                }
-            },
-            None => None,
-        },
-        number_form: sqlite_entry.number_form.map(|_true_var| NumberForm::Word),
-        number_type: match sqlite_entry.number_type {
-            Some(n_t) => match n_t {
-                0 => Some(NumberType::Cardinal),
-                1 => Some(NumberType::Ordinal),
-                _ => {
-                    dbg!(n_t);
-                    panic!("number_type not recognised");
-                }
-            },
-            None => None,
-        },
-        person: match sqlite_entry.person {
-            Some(person) => match person {
-                0 => Some(Person::One),
-                1 => Some(Person::Three),
-                2 => Some(Person::Two),
-                3 => Some(Person::Zero),
-                _ => {
-                    dbg!(person);
-                    panic!("person not recognised");
+
+impl LecsiconReader<File> {
+    pub fn open(filename: &Path) -> Result<LecsiconReader<File>, LecsiconError> {
+        Ok(LecsiconReader::new(File::open(filename)?))
+    }
+}
+
+impl<R: Read> Iterator for LecsiconReader<R> {
+    type Item = Result<LecsiconEntry, LecsiconError>;
+
+    fn next(&mut self) -> Option<Result<LecsiconEntry, LecsiconError>> {
+        if let Some(pending) = self.pending.pop_front() {
+            return Some(pending);
+        }
+        match self.records.next()? {
+            Ok(record) => {
+                let parsed = self.parse_record(record);
+                self.pending.push_back(parsed);
+                self.pending.pop_front()
+            }
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+}
+
+// Writes a feature as its integer code in the feature's own SQL type and
+//  reads it back, failing on a code with no value. Every variant is listed
+//  so that the match in `to_sql` is exhaustive: one left out here fails to
+//  compile rather than to decode.
+macro_rules! integer_coded_feature {
+    ($feature:ident, $sql_type:ident, $column:literal, $($variant:ident),+) => {
+        #[cfg(feature = "sqlite")]
+        impl ToSql<sql_types::$sql_type, Sqlite> for $feature {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+                out.set_value(match self {
+                    $($feature::$variant => $feature::$variant as i32,)+
+                });
+                Ok(IsNull::No)
+            }
+        }
+
+        #[cfg(feature = "sqlite")]
+        impl FromSql<sql_types::$sql_type, Sqlite> for $feature {
+            fn from_sql(value: SqliteValue<'_, '_, '_>) -> deserialize::Result<$feature> {
+                let code = <i32 as FromSql<Integer, Sqlite>>::from_sql(value)?;
+                $(if code == $feature::$variant as i32 {
+                    return Ok($feature::$variant);
+                })+
+                Err(LecsiconError::UndecodableRow {
+                    column: $column,
+                    value: code,
                 }
-            },
-            None => None,
-        },
-        polarity: sqlite_entry.polarity.map(|_true_var| Polarity::Negative),
-        polite: match sqlite_entry.polite {
-            Some(p) => match p {
-                0 => Some(Polite::Formal),
-                1 => Some(Polite::Informal),
-                _ => {
-                    dbg!(p);
-                    panic!("polite not recognised");
+                .into())
+            }
+        }
+    };
+}
+
+integer_coded_feature!(
+    Tag,
+    TagCode,
+    "tag",
+    Adjective,
+    Adposition,
+    Adverb,
+    Conjugation,
+    Determiner,
+    Interjection,
+    Noun,
+    Number,
+    Particle,
+    Pronoun,
+    ProperNoun,
+    Verb
+);
+integer_coded_feature!(
+    AdpositionType,
+    AdpositionTypeCode,
+    "adposition_type",
+    Preposition
+);
+integer_coded_feature!(Definite, DefiniteCode, "definite", Definite);
+integer_coded_feature!(
+    Degree,
+    DegreeCode,
+    "degree",
+    Comparative,
+    Equative,
+    Positive,
+    Superlative
+);
+integer_coded_feature!(
+    Gender,
+    GenderCode,
+    "gender",
+    Feminine,
+    FeminineMasculine,
+    Masculine
+);
+integer_coded_feature!(Mood, MoodCode, "mood", Imperative, Indicative, Subjunctive);
+integer_coded_feature!(
+    Mutation,
+    MutationCode,
+    "mutation",
+    Aspirate,
+    HProthesis,
+    Nasal,
+    Soft
+);
+integer_coded_feature!(NameType, NameTypeCode, "name_type", Geographical, Person);
+integer_coded_feature!(Number, NumberCode, "number", Collective, Plural, Singular);
+integer_coded_feature!(NumberForm, NumberFormCode, "number_form", Word);
+integer_coded_feature!(NumberType, NumberTypeCode, "number_type", Cardinal, Ordinal);
+integer_coded_feature!(Person, PersonCode, "person", Zero, One, Two, Three);
+integer_coded_feature!(Polarity, PolarityCode, "polarity", Negative);
+integer_coded_feature!(Polite, PoliteCode, "polite", Formal, Informal);
+integer_coded_feature!(Position, PositionCode, "position", Prenom);
+integer_coded_feature!(Possessive, PossessiveCode, "possessive", Yes);
+integer_coded_feature!(
+    PronounType,
+    PronounTypeCode,
+    "pronoun_type",
+    Article,
+    Demonstrative,
+    Interrogative,
+    Negative,
+    Personal,
+    Relative,
+    Reflexive
+);
+integer_coded_feature!(Relative, RelativeCode, "relative", Rel);
+integer_coded_feature!(Style, StyleCode, "style", Archaic, Colloquial, Formal);
+integer_coded_feature!(
+    Tense,
+    TenseCode,
+    "tense",
+    Future,
+    Imperfect,
+    Past,
+    Plusquamperfekt,
+    Present
+);
+integer_coded_feature!(VerbForm, VerbFormCode, "verb_form", Verbnoun);
+
+#[cfg(feature = "sqlite")]
+impl ToSql<Text, Sqlite> for FeatureSet {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        if self.is_empty() {
+            return Ok(IsNull::Yes);
+        }
+        out.set_value(self.to_string());
+        Ok(IsNull::No)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl FromSql<Nullable<Text>, Sqlite> for FeatureSet {
+    fn from_sql(value: SqliteValue<'_, '_, '_>) -> deserialize::Result<FeatureSet> {
+        let text = <String as FromSql<Text, Sqlite>>::from_sql(value)?;
+        Ok(FeatureSet::parse(&text))
+    }
+
+    fn from_nullable_sql(
+        value: Option<SqliteValue<'_, '_, '_>>,
+    ) -> deserialize::Result<FeatureSet> {
+        match value {
+            Some(value) => FeatureSet::from_sql(value),
+            None => Ok(FeatureSet::default()),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<LecsiconEntry> for NewLecsiconEntry {
+    fn from(entry: LecsiconEntry) -> NewLecsiconEntry {
+        NewLecsiconEntry {
+            search_key: search_key(&entry.word),
+            entry,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub(crate) const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+// SQLite's name for a new, empty database that only exists in memory.
+#[cfg(feature = "sqlite")]
+const MEMORY_DB: &str = ":memory:";
+
+// Numbers the in-memory databases made by this process.
+#[cfg(feature = "sqlite")]
+static MEMORY_COPIES: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "sqlite")]
+pub fn establish_connection(db_filepath: &Path) -> Result<SqliteConnection, LecsiconError> {
+    establish_connection_with_mode(db_filepath, OpenMode::ReadWrite)
+}
+
+#[cfg(feature = "sqlite")]
+pub fn establish_connection_with_mode(
+    db_filepath: &Path,
+    mode: OpenMode,
+) -> Result<SqliteConnection, LecsiconError> {
+    Ok(open_database(db_filepath, mode)?.0)
+}
+
+// Opens the database and returns the URL further connections to it should
+//  use. In-memory databases are memdb databases named for this process, so
+//  that every connection sees the same one for as long as the first keeps
+//  it open; ":memory:" gets an empty one, so it can't be opened read-only.
+#[cfg(feature = "sqlite")]
+fn open_database(
+    db_filepath: &Path,
+    mode: OpenMode,
+) -> Result<(SqliteConnection, String), LecsiconError> {
+    let in_memory = db_filepath == Path::new(MEMORY_DB);
+    if in_memory && matches!(mode, OpenMode::ReadOnly | OpenMode::Immutable) {
+        return Err(LecsiconError::InvalidOpenMode(mode));
+    }
+    if !in_memory && !db_filepath.exists() {
+        return Err(LecsiconError::MissingDatabase(db_filepath.to_path_buf()));
+    }
+    let url = if in_memory || mode == OpenMode::Memory {
+        format!(
+            "file:/lecsicon-{}-{}?vfs=memdb",
+            std::process::id(),
+            MEMORY_COPIES.fetch_add(1, Ordering::Relaxed)
+        )
+    } else {
+        match mode {
+            OpenMode::ReadOnly => file_uri(db_filepath, "mode=ro")?,
+            OpenMode::Immutable => file_uri(db_filepath, "mode=ro&immutable=1")?,
+            _ => path_as_str(db_filepath)?.to_string(),
+        }
+    };
+    let mut conn = SqliteConnection::establish(&url)?;
+    if in_memory {
+        prepare_connection(&mut conn)?;
+        return Ok((conn, url));
+    }
+    match mode {
+        OpenMode::ReadWrite => prepare_connection(&mut conn)?,
+        // Diesel doesn't hand out its SQLite handle, so the backup API
+        //  fills the copy through a second connection
+        OpenMode::Memory => {
+            backup_database(path_as_str(db_filepath)?, &url)?;
+            prepare_connection(&mut conn)?;
+        }
+        OpenMode::ReadOnly | OpenMode::Immutable => check_schema(&mut conn, true)?,
+    }
+    Ok((conn, url))
+}
+
+// SQLite reads ?, # and % in a URI path specially, so those are escaped.
+//  An absolute path gets an empty authority, so that one starting with //
+//  isn't read as a host name.
+#[cfg(feature = "sqlite")]
+fn file_uri(db_filepath: &Path, parameters: &str) -> Result<String, LecsiconError> {
+    let path = path_as_str(db_filepath)?;
+    let mut uri = String::from(if path.starts_with('/') {
+        "file://"
+    } else {
+        "file:"
+    });
+    for letter in path.chars() {
+        match letter {
+            '?' => uri.push_str("%3f"),
+            '#' => uri.push_str("%23"),
+            '%' => uri.push_str("%25"),
+            _ => uri.push(letter),
+        }
+    }
+    uri.push('?');
+    uri.push_str(parameters);
+    Ok(uri)
+}
+
+// SQLite creates the file if it isn't there; the migrations then build
+//  or update the schema.
+#[cfg(feature = "sqlite")]
+pub fn create_or_open(db_filepath: &Path) -> Result<SqliteConnection, LecsiconError> {
+    if db_filepath == Path::new(MEMORY_DB) {
+        return establish_connection_with_mode(db_filepath, OpenMode::Memory);
+    }
+    let mut conn = SqliteConnection::establish(path_as_str(db_filepath)?)?;
+    prepare_connection(&mut conn)?;
+    Ok(conn)
+}
+
+// Applied to each pooled connection when it is opened.
+#[cfg(feature = "sqlite")]
+#[derive(Debug)]
+struct PooledConnectionOptions {
+    writable: bool,
+}
+
+#[cfg(feature = "sqlite")]
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for PooledConnectionOptions {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        // wait for a write through another connection to finish instead of
+        //  failing straight away
+        conn.batch_execute("PRAGMA busy_timeout = 5000;")?;
+        if self.writable {
+            conn.batch_execute("PRAGMA foreign_keys = ON;")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Lexicon {
+    pub fn open(db_filepath: &Path) -> Result<Lexicon, LecsiconError> {
+        Lexicon::open_with_mode(db_filepath, OpenMode::ReadWrite)
+    }
+
+    // The first connection runs the migrations, checks the schema or makes
+    //  the in-memory copy before the pool opens any others.
+    pub fn open_with_mode(db_filepath: &Path, mode: OpenMode) -> Result<Lexicon, LecsiconError> {
+        let (conn, url) = open_database(db_filepath, mode)?;
+        let pool = Pool::builder()
+            .connection_customizer(Box::new(PooledConnectionOptions {
+                writable: !matches!(mode, OpenMode::ReadOnly | OpenMode::Immutable),
+            }))
+            .build(ConnectionManager::new(url))?;
+        let in_memory = mode == OpenMode::Memory || db_filepath == Path::new(MEMORY_DB);
+        Ok(Lexicon {
+            pool,
+            _anchor: in_memory.then(|| Arc::new(Mutex::new(conn))),
+        })
+    }
+
+    pub fn create_or_open(db_filepath: &Path) -> Result<Lexicon, LecsiconError> {
+        if db_filepath != Path::new(MEMORY_DB) {
+            create_or_open(db_filepath)?;
+        }
+        Lexicon::open(db_filepath)
+    }
+
+    // For the functions that take a connection, such as the exports. It
+    //  goes back to the pool when dropped.
+    pub fn connection(
+        &self,
+    ) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, LecsiconError> {
+        Ok(self.pool.get()?)
+    }
+
+    pub fn search(&self, word: &str) -> Result<Vec<LecsiconEntry>, LecsiconError> {
+        self.search_with_mode(word, SearchMode::Exact)
+    }
+
+    pub fn search_with_mode(
+        &self,
+        word: &str,
+        mode: SearchMode,
+    ) -> Result<Vec<LecsiconEntry>, LecsiconError> {
+        let mut conn = self.connection()?;
+        search_entries(word, mode, &mut conn)
+    }
+
+    pub fn related(&self, entry: LecsiconEntry) -> Result<Vec<LecsiconEntry>, LecsiconError> {
+        let mut conn = self.connection()?;
+        get_related_entries(entry, &mut conn)
+    }
+
+    pub fn suggest(&self, prefix: &str) -> Result<Vec<String>, LecsiconError> {
+        let mut conn = self.connection()?;
+        Ok(get_words_by_prefix(prefix, &mut conn)?)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn path_as_str(db_filepath: &Path) -> Result<&str, LecsiconError> {
+    db_filepath
+        .to_str()
+        .ok_or_else(|| LecsiconError::MissingDatabase(db_filepath.to_path_buf()))
+}
+
+#[cfg(feature = "sqlite")]
+fn prepare_connection(conn: &mut SqliteConnection) -> Result<(), LecsiconError> {
+    migrate(conn)?;
+    // SQLite leaves the references to the code tables unchecked otherwise
+    conn.batch_execute("PRAGMA foreign_keys = ON;")?;
+    Ok(())
+}
+
+// A bare SQLite handle, closed when dropped. It owns its handle alone: it
+//  is never cloned, and the raw pointer keeps it on the thread that opened
+//  it, so nothing else can use or close the connection while it is alive.
+#[cfg(feature = "sqlite")]
+struct RawDatabase(*mut ffi::sqlite3);
+
+#[cfg(feature = "sqlite")]
+impl RawDatabase {
+    fn open(path: &str, flags: c_int) -> Result<RawDatabase, LecsiconError> {
+        let c_path = CString::new(path)
+            .map_err(|_| LecsiconError::Backup(format!("{path}: path contains a NUL byte")))?;
+        let mut handle = std::ptr::null_mut();
+        // SAFETY: c_path is a NUL-terminated string that outlives the call,
+        //  handle is a valid place for SQLite to write the new handle to, and
+        //  a null VFS name selects the default VFS.
+        let status = unsafe {
+            ffi::sqlite3_open_v2(
+                c_path.as_ptr(),
+                &mut handle,
+                flags | ffi::SQLITE_OPEN_URI,
+                std::ptr::null(),
+            )
+        };
+        // SQLite usually returns a handle even when opening fails, and it
+        //  still has to be closed
+        let database = RawDatabase(handle);
+        if status != ffi::SQLITE_OK {
+            return Err(database.error());
+        }
+        Ok(database)
+    }
+
+    fn error(&self) -> LecsiconError {
+        // SAFETY: self.0 is this struct's own handle, still open, or null if
+        //  SQLite could not allocate one, for which sqlite3_errmsg returns a
+        //  static message. The message is copied out before anything else
+        //  can use the handle and replace it.
+        let message = unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(self.0)) };
+        LecsiconError::Backup(message.to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Drop for RawDatabase {
+    fn drop(&mut self) {
+        // SAFETY: the handle is closed only here, once, when its sole owner
+        //  goes; any backup using it has been finished by then, and closing
+        //  null does nothing.
+        unsafe {
+            ffi::sqlite3_close(self.0);
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn backup_database(source_path: &str, destination_path: &str) -> Result<(), LecsiconError> {
+    let source = RawDatabase::open(source_path, ffi::SQLITE_OPEN_READONLY)?;
+    let destination = RawDatabase::open(destination_path, ffi::SQLITE_OPEN_READWRITE)?;
+    // SAFETY: source and destination are two distinct open handles, as the
+    //  backup API requires, owned by this function and used by nothing else
+    //  until they drop at its end. The backup object is finished before then
+    //  on every path that got one, and is not used after finishing. The
+    //  schema names are NUL-terminated literals.
+    unsafe {
+        let backup =
+            ffi::sqlite3_backup_init(destination.0, c"main".as_ptr(), source.0, c"main".as_ptr());
+        if backup.is_null() {
+            return Err(destination.error());
+        }
+        // -1 copies every page in one step; finishing reports any error
+        //  the step hit
+        ffi::sqlite3_backup_step(backup, -1);
+        if ffi::sqlite3_backup_finish(backup) != ffi::SQLITE_OK {
+            return Err(destination.error());
+        }
+    }
+    Ok(())
+}
+
+// Runs before foreign keys are switched on, since some migrations rebuild
+//  tables that others reference.
+#[cfg(feature = "sqlite")]
+fn migrate(conn: &mut SqliteConnection) -> Result<(), LecsiconError> {
+    check_schema(conn, false)?;
+    conn.run_pending_migrations(MIGRATIONS)
+        .map_err(LecsiconError::Migration)?;
+    Ok(())
+}
+
+// A database may never have migrations this crate doesn't know. One that
+//  can't be migrated must also already have all of those it does know.
+#[cfg(feature = "sqlite")]
+fn check_schema(conn: &mut SqliteConnection, require_current: bool) -> Result<(), LecsiconError> {
+    let known = MigrationSource::<Sqlite>::migrations(&MIGRATIONS)
+        .map_err(LecsiconError::Migration)?
+        .iter()
+        .map(|migration| migration.name().version().as_owned())
+        .collect::<Vec<MigrationVersion>>();
+    let applied = conn
+        .applied_migrations()
+        .map_err(LecsiconError::Migration)?;
+    if let Some(unknown) = applied.iter().find(|version| !known.contains(version)) {
+        return Err(LecsiconError::UnsupportedSchema(unknown.to_string()));
+    }
+    if require_current {
+        if let Some(missing) = known.iter().find(|version| !applied.contains(version)) {
+            return Err(LecsiconError::OutdatedSchema(missing.to_string()));
+        }
+    }
+    Ok(())
+}
+
+// The number of migrations applied, which for a database opened through
+//  this crate is SCHEMA_VERSION.
+#[cfg(feature = "sqlite")]
+pub fn get_schema_version(conn: &mut SqliteConnection) -> Result<i32, LecsiconError> {
+    Ok(conn
+        .applied_migrations()
+        .map_err(LecsiconError::Migration)?
+        .len() as i32)
+}
+
+// Vowels as Welsh writes them with a circumflex, grave, acute or
+//  diaeresis, in either case, and the letter each folds to.
+#[cfg(feature = "sqlite")]
+const ACCENTED_VOWELS: [(&str, char); 7] = [
+    ("âàáäÂÀÁÄ", 'a'),
+    ("êèéëÊÈÉË", 'e'),
+    ("îìíïÎÌÍÏ", 'i'),
+    ("ôòóöÔÒÓÖ", 'o'),
+    ("ûùúüÛÙÚÜ", 'u'),
+    ("ŵẁẃẅŴẀẂẄ", 'w'),
+    ("ŷỳýÿŶỲÝŸ", 'y'),
+];
+
+// The same accents as combining characters, dropped when folding.
+#[cfg(feature = "sqlite")]
+const COMBINING_ACCENTS: [char; 4] = ['\u{300}', '\u{301}', '\u{302}', '\u{308}'];
+
+// The key folded lookups match on: ASCII letters in lower case and the
+//  accented vowels without their accents. It must agree with the
+//  add_search_key migration, which fills the column for existing rows
+//  using SQLite's lower(), so other letters keep their case.
+#[cfg(feature = "sqlite")]
+pub fn search_key(word: &str) -> String {
+    word.chars()
+        .filter(|letter| !COMBINING_ACCENTS.contains(letter))
+        .map(|letter| {
+            ACCENTED_VOWELS
+                .iter()
+                .find(|(accented, _)| accented.contains(letter))
+                .map_or(letter.to_ascii_lowercase(), |(_, vowel)| *vowel)
+        })
+        .collect()
+}
+
+#[cfg(feature = "sqlite")]
+pub fn search_entries(
+    query: &str,
+    mode: SearchMode,
+    conn: &mut SqliteConnection,
+) -> Result<Vec<LecsiconEntry>, LecsiconError> {
+    let mut entries = match mode {
+        SearchMode::Exact => get_lecsicon_entries_by_word(query, conn)?,
+        SearchMode::Folded => crate::schema::lecsicon::table
+            .left_join(crate::schema::lemmas::table)
+            .filter(crate::schema::lecsicon::search_key.eq(search_key(query)))
+            .select(LecsiconEntry::as_select())
+            .load(conn)?,
+    };
+    if entries.is_empty() {
+        return Err(LecsiconError::NotFound(query.to_string()));
+    }
+    if mode == SearchMode::Folded {
+        // the word as typed, then the same letters in another case, then
+        //  spellings with other accents
+        let lowercase_query = query.to_lowercase();
+        entries.sort_by_cached_key(|entry| {
+            let rank = if entry.word == query {
+                0
+            } else if entry.word.to_lowercase() == lowercase_query {
+                1
+            } else {
+                2
+            };
+            (rank, entry.word.clone(), entry.id)
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(feature = "sqlite")]
+pub fn get_lecsicon_entries_by_word(
+    word: &str,
+    conn: &mut SqliteConnection,
+) -> QueryResult<Vec<LecsiconEntry>> {
+    crate::schema::lecsicon::table
+        .left_join(crate::schema::lemmas::table)
+        .filter(crate::schema::lecsicon::word.eq(word))
+        .order(crate::schema::lecsicon::id)
+        .select(LecsiconEntry::as_select())
+        .load(conn)
+}
+
+// Distinct words starting with the prefix, in byte order. Written as a
+//  range rather than LIKE, which SQLite cannot answer from the word index
+//  because LIKE ignores ASCII case; the range is case-sensitive, so "ca"
+//  does not suggest "Caerdydd". `get_words_by_folded_prefix` ignores case.
+#[cfg(feature = "sqlite")]
+pub fn get_words_by_prefix(prefix: &str, conn: &mut SqliteConnection) -> QueryResult<Vec<String>> {
+    use crate::schema::lecsicon::dsl::*;
+
+    lecsicon
+        .select(word)
+        .filter(word.ge(prefix))
+        .filter(word.lt(format!("{prefix}\u{10FFFF}")))
+        .distinct()
+        .order(word)
+        .load(conn)
+}
+
+// Distinct words containing the fragment anywhere, ignoring case, in byte
+//  order. Fragments of three or more characters are looked up in the
+//  trigram index; shorter ones, which it cannot answer, scan the table
+//  and ignore only ASCII case, as SQLite's lower() does.
+#[cfg(feature = "sqlite")]
+pub fn get_words_containing(
+    fragment: &str,
+    conn: &mut SqliteConnection,
+) -> Result<Vec<String>, LecsiconError> {
+    if fragment.is_empty() {
+        return Ok(vec![]);
+    }
+    let rows: Vec<WordRow> = if fragment.chars().count() >= 3 {
+        // quoted as a phrase so that the fragment is matched literally
+        let phrase = format!("\"{}\"", fragment.replace('"', "\"\""));
+        diesel::sql_query(
+            "SELECT DISTINCT word FROM lecsicon_trigrams \
+             WHERE lecsicon_trigrams MATCH ? ORDER BY word",
+        )
+        .bind::<diesel::sql_types::Text, _>(phrase)
+        .load(conn)?
+    } else {
+        diesel::sql_query(
+            "SELECT DISTINCT word FROM lecsicon \
+             WHERE instr(lower(word), lower(?)) > 0 ORDER BY word",
+        )
+        .bind::<diesel::sql_types::Text, _>(fragment)
+        .load(conn)?
+    };
+    Ok(rows.into_iter().map(|row| row.word).collect())
+}
+
+// As `get_words_by_prefix`, but matching the prefix on folded words.
+#[cfg(feature = "sqlite")]
+pub fn get_words_by_folded_prefix(
+    prefix: &str,
+    conn: &mut SqliteConnection,
+) -> QueryResult<Vec<String>> {
+    use crate::schema::lecsicon;
+
+    let folded_prefix = search_key(prefix);
+    lecsicon::table
+        .select(lecsicon::word)
+        .filter(lecsicon::search_key.ge(&folded_prefix))
+        .filter(lecsicon::search_key.lt(format!("{folded_prefix}\u{10FFFF}")))
+        .distinct()
+        .order(lecsicon::word)
+        .load(conn)
+}
+
+// Ordered by word and id, as the snapshot gives them.
+#[cfg(feature = "sqlite")]
+pub fn get_related_entries(
+    entry: LecsiconEntry,
+    db_connection: &mut SqliteConnection,
+) -> Result<Vec<LecsiconEntry>, LecsiconError> {
+    // entries of the lemma spelt like this entry's word, with the same tag
+    let lemma_entries = crate::schema::lecsicon::table
+        .inner_join(crate::schema::lemmas::table)
+        .filter(crate::schema::lemmas::lemma.eq(&entry.word))
+        .filter(crate::schema::lemmas::tag.eq(&entry.tag))
+        .order((crate::schema::lecsicon::word, crate::schema::lecsicon::id))
+        .select(LecsiconEntry::as_select())
+        .into_boxed();
+    let mut entries: Vec<LecsiconEntry> = match entry.tag {
+        Some(Tag::Verb) => {
+            if entry.verb_form.is_some() {
+                // Berfenw
+                lemma_entries.load(db_connection)?
+            } else {
+                // Berf yn unig
+                lemma_entries
+                    .filter(crate::schema::lecsicon::mood.eq(&entry.mood))
+                    .filter(crate::schema::lecsicon::tense.eq(&entry.tense))
+                    .filter(crate::schema::lecsicon::number.eq(&entry.number))
+                    .filter(crate::schema::lecsicon::person.eq(&entry.person))
+                    .load(db_connection)?
+            }
+        }
+        _ => lemma_entries.load(db_connection)?,
+    };
+
+    // filter out the entry that these related entries
+    //  are linked to (don't return itself)
+    entries.retain(|e| *e != entry);
+    if entries.is_empty() {
+        Err(LecsiconError::NotFound(entry.word))
+    } else {
+        Ok(entries)
+    }
+}
+
+// Entries with the given feature value. Typed features are matched on
+//  their column, any other feature within the other_features text.
+#[cfg(feature = "sqlite")]
+pub fn get_entries_by_feature(
+    name: &str,
+    value: &str,
+    conn: &mut SqliteConnection,
+) -> Result<Vec<LecsiconEntry>, LecsiconError> {
+    use crate::schema::lecsicon::dsl::*;
+
+    let pair = format!("{name}={value}");
+    let mut probe = LecsiconEntry {
+        ..Default::default()
+    };
+    let query = if probe.apply_ud_feature(&pair) {
+        let query = lecsicon
+            .left_join(crate::schema::lemmas::table)
+            .into_boxed();
+        match name {
+            "AdpType" => query.filter(adposition_type.eq(probe.adposition_type)),
+            "Definite" => query.filter(definite.eq(probe.definite)),
+            "Degree" => query.filter(degree.eq(probe.degree)),
+            "Gender" => query.filter(gender.eq(probe.gender)),
+            "Mood" => query.filter(mood.eq(probe.mood)),
+            "Mutation" => query.filter(mutation.eq(probe.mutation)),
+            "NameType" => query.filter(name_type.eq(probe.name_type)),
+            "Number" => query.filter(number.eq(probe.number)),
+            "Numform" => query.filter(number_form.eq(probe.number_form)),
+            "Numtype" => query.filter(number_type.eq(probe.number_type)),
+            "Person" => query.filter(person.eq(probe.person)),
+            "Polarity" => query.filter(polarity.eq(probe.polarity)),
+            "Polite" => query.filter(polite.eq(probe.polite)),
+            "Position" => query.filter(position.eq(probe.position)),
+            "Poss" => query.filter(possessive.eq(probe.possessive)),
+            "PronType" => query.filter(pronoun_type.eq(probe.pronoun_type)),
+            "Relative" => query.filter(relative.eq(probe.relative)),
+            "Style" => query.filter(style.eq(probe.style)),
+            "Tense" => query.filter(tense.eq(probe.tense)),
+            "VerbForm" => query.filter(verb_form.eq(probe.verb_form)),
+            _ => return Err(LecsiconError::NotFound(pair)),
+        }
+    } else {
+        // stored as `A=1|B=2`, so the pair may open, close, sit inside or be the whole text
+        let escaped = escape_like(&pair);
+        lecsicon
+            .left_join(crate::schema::lemmas::table)
+            .filter(
+                other_features
+                    .eq(&pair)
+                    .or(other_features.like(format!("{escaped}|%")).escape('\\'))
+                    .or(other_features.like(format!("%|{escaped}")).escape('\\'))
+                    .or(other_features.like(format!("%|{escaped}|%")).escape('\\')),
+            )
+            .into_boxed()
+    };
+    let entries = query
+        .order((word, id))
+        .select(LecsiconEntry::as_select())
+        .load(conn)?;
+    if entries.is_empty() {
+        Err(LecsiconError::NotFound(pair))
+    } else {
+        Ok(entries)
+    }
+}
+
+// Lemmas in lemma order, each with the number of entries referring to it.
+#[cfg(feature = "sqlite")]
+pub fn get_lemmas(conn: &mut SqliteConnection) -> Result<Vec<Lemma>, LecsiconError> {
+    load_lemmas(conn, None)
+}
+
+#[cfg(feature = "sqlite")]
+pub fn get_lemma(lemma_id: i32, conn: &mut SqliteConnection) -> Result<Lemma, LecsiconError> {
+    load_lemmas(conn, Some(lemma_id))?
+        .pop()
+        .ok_or_else(|| LecsiconError::NotFound(format!("lemma {lemma_id}")))
+}
+
+#[cfg(feature = "sqlite")]
+fn load_lemmas(
+    conn: &mut SqliteConnection,
+    lemma_id: Option<i32>,
+) -> Result<Vec<Lemma>, LecsiconError> {
+    use crate::schema::{lecsicon, lemmas};
+
+    let mut query = lemmas::table
+        .left_join(lecsicon::table)
+        .group_by(lemmas::id)
+        .select((
+            lemmas::id,
+            lemmas::lemma,
+            lemmas::tag,
+            diesel::dsl::count(lecsicon::id.nullable()),
+        ))
+        .order((lemmas::lemma, lemmas::tag))
+        .into_boxed();
+    if let Some(lemma_id) = lemma_id {
+        query = query.filter(lemmas::id.eq(lemma_id));
+    }
+    Ok(query
+        .load::<(i32, String, Option<Tag>, i64)>(conn)?
+        .into_iter()
+        .map(|(id, lemma, tag, forms)| Lemma {
+            id,
+            lemma,
+            tag,
+            forms: forms as usize,
+        })
+        .collect())
+}
+
+// Every entry of a lemma, in word order.
+#[cfg(feature = "sqlite")]
+pub fn get_lemma_forms(
+    lemma_id: i32,
+    conn: &mut SqliteConnection,
+) -> Result<Vec<LecsiconEntry>, LecsiconError> {
+    let entries = crate::schema::lecsicon::table
+        .inner_join(crate::schema::lemmas::table)
+        .filter(crate::schema::lecsicon::lemma_id.eq(lemma_id))
+        .order((crate::schema::lecsicon::word, crate::schema::lecsicon::id))
+        .select(LecsiconEntry::as_select())
+        .load(conn)?;
+    if entries.is_empty() {
+        Err(LecsiconError::NotFound(format!("lemma {lemma_id}")))
+    } else {
+        Ok(entries)
+    }
+}
+
+// Finds or adds the lemmas row for each entry's lemma and tag, remembering
+//  the ids so that each lemma is looked up once per import.
+#[cfg(feature = "sqlite")]
+#[derive(Default)]
+struct LemmaIds(HashMap<(String, Option<Tag>), i32>);
+
+#[cfg(feature = "sqlite")]
+impl LemmaIds {
+    fn resolve(
+        &mut self,
+        conn: &mut SqliteConnection,
+        entry: &mut LecsiconEntry,
+    ) -> Result<(), LecsiconError> {
+        use crate::schema::lemmas::dsl::*;
+
+        let Some(entry_lemma) = &entry.lemma else {
+            entry.lemma_id = None;
+            return Ok(());
+        };
+        let key = (entry_lemma.clone(), entry.tag.clone());
+        if let Some(known_id) = self.0.get(&key) {
+            entry.lemma_id = Some(*known_id);
+            return Ok(());
+        }
+        diesel::insert_into(lemmas)
+            .values((lemma.eq(entry_lemma), tag.eq(&entry.tag)))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        let found_id = lemmas
+            .select(id)
+            .filter(lemma.eq(entry_lemma))
+            .filter(tag.is(&entry.tag))
+            .first(conn)?;
+        self.0.insert(key, found_id);
+        entry.lemma_id = Some(found_id);
+        Ok(())
+    }
+}
+
+// 1000 rows of 30 columns stays below SQLite's limit of 32766 bound parameters per statement.
+#[cfg(feature = "sqlite")]
+const INSERT_BATCH_SIZE: usize = 1000;
+
+#[cfg(feature = "sqlite")]
+fn insert_batch(
+    conn: &mut SqliteConnection,
+    batch: &mut Vec<NewLecsiconEntry>,
+) -> Result<(), LecsiconError> {
+    diesel::insert_into(crate::schema::lecsicon::table)
+        .values(&*batch)
+        .execute(conn)?;
+    batch.clear();
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+fn sha256_of_file(file_path: &Path) -> Result<String, LecsiconError> {
+    let mut file = File::open(file_path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(feature = "sqlite")]
+fn record_import(
+    conn: &mut SqliteConnection,
+    source_path: &Path,
+    summary: &ImportSummary,
+) -> Result<(), LecsiconError> {
+    let rows_total: i64 = crate::schema::lecsicon::table.count().get_result(conn)?;
+    diesel::insert_into(crate::schema::lecsicon_metadata::table)
+        .values(NewImportMetadata {
+            source_path: source_path.to_string_lossy().into_owned(),
+            source_sha256: sha256_of_file(source_path)?,
+            rows_read: summary.rows_read as i32,
+            rows_total: rows_total as i32,
+            rows_rejected: summary.rows_rejected as i32,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: SCHEMA_VERSION,
+        })
+        .execute(conn)?;
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+impl LecsiconError {
+    // Errors tied to a single CSV line, which a lenient import sets aside
+    //  instead of aborting on.
+    fn as_reject(&self) -> Option<NewLecsiconReject> {
+        match self {
+            LecsiconError::MissingField { line, record, .. }
+            | LecsiconError::UnrecognisedTag { line, record, .. }
+            | LecsiconError::UnrecognisedFeature { line, record, .. } => Some(NewLecsiconReject {
+                line: *line as i32,
+                raw_line: record.clone(),
+                reason: self.to_string(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+// Passes entries through, diverting line-level errors into `rejects` when
+//  the import is lenient. The summary counts every line seen.
+#[cfg(feature = "sqlite")]
+fn screen_entries<'a, I>(
+    data: I,
+    mode: ImportMode,
+    rejects: &'a mut Vec<NewLecsiconReject>,
+    summary: &'a mut ImportSummary,
+) -> impl Iterator<Item = Result<LecsiconEntry, LecsiconError>> + 'a
+where
+    I: IntoIterator<Item = Result<LecsiconEntry, LecsiconError>>,
+    I::IntoIter: 'a,
+{
+    data.into_iter().filter_map(move |result| match result {
+        Ok(entry) => {
+            summary.rows_read += 1;
+            summary.rows_imported += 1;
+            Some(Ok(entry))
+        }
+        Err(e) => match (mode, e.as_reject()) {
+            (ImportMode::Lenient, Some(reject)) => {
+                if let LecsiconError::UnrecognisedFeature { .. } = e {
+                    summary.features_rejected += 1;
+                } else {
+                    summary.rows_read += 1;
+                    summary.rows_rejected += 1;
                 }
-            },
-            None => None,
+                rejects.push(reject);
+                None
+            }
+            _ => Some(Err(e)),
         },
-        position: sqlite_entry.position.map(|_true_var| Position::Prenom),
-        possessive: sqlite_entry.possessive.map(|_true_var| Possessive::Yes),
-        pronoun_type: match sqlite_entry.pronoun_type {
-            Some(p_t) => match p_t {
-                0 => Some(PronounType::Article),
-                1 => Some(PronounType::Demonstrative),
-                2 => Some(PronounType::Interrogative),
-                3 => Some(PronounType::Negative),
-                4 => Some(PronounType::Personal),
-                5 => Some(PronounType::Reflexive),
-                6 => Some(PronounType::Relative),
-                _ => {
-                    dbg!(p_t);
-                    panic!("pron_type not recognised");
+    })
+}
+
+#[cfg(feature = "sqlite")]
+fn save_rejects(
+    conn: &mut SqliteConnection,
+    rejects: &[NewLecsiconReject],
+) -> Result<(), LecsiconError> {
+    diesel::delete(crate::schema::lecsicon_rejects::table).execute(conn)?;
+    for batch in rejects.chunks(INSERT_BATCH_SIZE) {
+        diesel::insert_into(crate::schema::lecsicon_rejects::table)
+            .values(batch)
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+pub fn get_rejects(conn: &mut SqliteConnection) -> QueryResult<Vec<LecsiconReject>> {
+    crate::schema::lecsicon_rejects::table
+        .order(crate::schema::lecsicon_rejects::id)
+        .load(conn)
+}
+
+#[cfg(feature = "sqlite")]
+pub fn get_latest_import(conn: &mut SqliteConnection) -> QueryResult<Option<ImportMetadata>> {
+    crate::schema::lecsicon_metadata::table
+        .order(crate::schema::lecsicon_metadata::id.desc())
+        .first(conn)
+        .optional()
+}
+
+#[cfg(feature = "sqlite")]
+pub fn get_import_history(conn: &mut SqliteConnection) -> QueryResult<Vec<ImportMetadata>> {
+    crate::schema::lecsicon_metadata::table
+        .order(crate::schema::lecsicon_metadata::id.desc())
+        .load(conn)
+}
+
+// Indexing each word's trigrams as its row is inserted more than doubles
+//  the time a whole lexicon takes to import, so an import into an empty
+//  table drops the trigger that does it and rebuilds the index once at the
+//  end. Returns the trigger's SQL, for putting it back in the same
+//  transaction, or None if the trigger was left in place.
+#[cfg(feature = "sqlite")]
+fn suspend_trigram_trigger(conn: &mut SqliteConnection) -> Result<Option<String>, LecsiconError> {
+    let has_entries: bool = diesel::select(diesel::dsl::exists(
+        crate::schema::lecsicon::table.select(crate::schema::lecsicon::id),
+    ))
+    .get_result(conn)?;
+    if has_entries {
+        return Ok(None);
+    }
+    let trigger_sql: String = diesel::select(diesel::dsl::sql::<Text>(
+        "(SELECT sql FROM sqlite_master \
+         WHERE type = 'trigger' AND name = 'lecsicon_trigrams_insert')",
+    ))
+    .get_result(conn)?;
+    conn.batch_execute("DROP TRIGGER lecsicon_trigrams_insert;")?;
+    Ok(Some(trigger_sql))
+}
+
+#[cfg(feature = "sqlite")]
+pub fn save_data_to_sqlite_db<I>(
+    data: I,
+    db_file_path: &Path,
+    source_path: Option<&Path>,
+    mode: ImportMode,
+) -> Result<ImportSummary, LecsiconError>
+where
+    I: IntoIterator<Item = Result<LecsiconEntry, LecsiconError>>,
+{
+    // a file made for this import is removed again if the import fails, so
+    //  that nothing is left behind
+    let created = db_file_path != Path::new(MEMORY_DB) && !db_file_path.exists();
+    let result = create_or_open(db_file_path)
+        .and_then(|mut conn| save_data_to_connection(data, &mut conn, source_path, mode));
+    if result.is_err() && created {
+        let _ = std::fs::remove_file(db_file_path);
+    }
+    result
+}
+
+#[cfg(feature = "sqlite")]
+pub fn save_data_to_connection<I>(
+    data: I,
+    conn: &mut SqliteConnection,
+    source_path: Option<&Path>,
+    mode: ImportMode,
+) -> Result<ImportSummary, LecsiconError>
+where
+    I: IntoIterator<Item = Result<LecsiconEntry, LecsiconError>>,
+{
+    conn.batch_execute("PRAGMA cache_size = -65536; PRAGMA temp_store = MEMORY;")?;
+    conn.immediate_transaction(|conn| {
+        let trigram_trigger = suspend_trigram_trigger(conn)?;
+        let mut summary = ImportSummary::default();
+        let mut rejects: Vec<NewLecsiconReject> = vec![];
+        let mut batch: Vec<NewLecsiconEntry> = Vec::with_capacity(INSERT_BATCH_SIZE);
+        let mut lemma_ids = LemmaIds::default();
+        for text_entry in screen_entries(data, mode, &mut rejects, &mut summary) {
+            let mut entry = text_entry?;
+            lemma_ids.resolve(conn, &mut entry)?;
+            batch.push(entry.into());
+            if batch.len() == INSERT_BATCH_SIZE {
+                insert_batch(conn, &mut batch)?;
+            }
+        }
+        insert_batch(conn, &mut batch)?;
+        if let Some(trigger_sql) = trigram_trigger {
+            conn.batch_execute(
+                "INSERT INTO lecsicon_trigrams (lecsicon_trigrams) VALUES ('rebuild');",
+            )?;
+            conn.batch_execute(&trigger_sql)?;
+        }
+        save_rejects(conn, &rejects)?;
+        if let Some(source_path) = source_path {
+            record_import(conn, source_path, &summary)?;
+        }
+        Ok(summary)
+    })
+}
+
+// An analysis is identified by its word and full feature set; the id,
+//  lemma and source text are what an update may change.
+#[cfg(feature = "sqlite")]
+fn analysis_key(entry: &LecsiconEntry) -> LecsiconEntry {
+    LecsiconEntry {
+        id: 0,
+        lemma: None,
+        lemma_id: None,
+        source_line: None,
+        raw_features: None,
+        extra_fields: None,
+        ..entry.clone()
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn diff_with_sqlite_db<I>(
+    data: I,
+    conn: &mut SqliteConnection,
+) -> Result<SqliteChanges, LecsiconError>
+where
+    I: IntoIterator<Item = Result<LecsiconEntry, LecsiconError>>,
+{
+    let mut existing: HashMap<LecsiconEntry, Vec<LecsiconEntry>> = HashMap::new();
+    let mut next_ids: HashMap<String, i32> = HashMap::new();
+    let mut all_existing: Vec<LecsiconEntry> = crate::schema::lecsicon::table
+        .left_join(crate::schema::lemmas::table)
+        .order((crate::schema::lecsicon::word, crate::schema::lecsicon::id))
+        .select(LecsiconEntry::as_select())
+        .load(conn)?;
+    for entry in all_existing.drain(..) {
+        let next_id = next_ids.entry(entry.word.clone()).or_insert(101);
+        *next_id = (*next_id).max(entry.id + 1);
+        existing
+            .entry(analysis_key(&entry))
+            .or_default()
+            .push(entry);
+    }
+
+    let mut unmatched: HashMap<LecsiconEntry, Vec<LecsiconEntry>> = HashMap::new();
+    let mut moved: Vec<LecsiconEntry> = vec![];
+    for entry in data {
+        let entry = entry?;
+        let key = analysis_key(&entry);
+        let unchanged = existing.get_mut(&key).and_then(|old_entries| {
+            old_entries
+                .iter()
+                .position(|e| {
+                    e.lemma == entry.lemma
+                        && e.raw_features == entry.raw_features
+                        && e.extra_fields == entry.extra_fields
+                })
+                .map(|index| old_entries.remove(index))
+        });
+        match unchanged {
+            Some(old_entry) => {
+                if old_entry.source_line != entry.source_line {
+                    moved.push(LecsiconEntry {
+                        id: old_entry.id,
+                        lemma_id: old_entry.lemma_id,
+                        ..entry
+                    });
                 }
-            },
-            None => None,
-        },
-        relative: sqlite_entry.relative.map(|_true_var| Relative::Rel),
-        style: match sqlite_entry.style {
-            Some(s) => match s {
-                0 => Some(Style::Archaic),
-                1 => Some(Style::Colloquial),
-                2 => Some(Style::Formal),
-                _ => {
-                    dbg!(s);
-                    panic!("style not recognised");
+            }
+            None => unmatched.entry(key).or_default().push(entry),
+        }
+    }
+
+    let mut changes = SqliteChanges {
+        added: vec![],
+        removed: vec![],
+        changed: vec![],
+        moved,
+    };
+    for (key, new_entries) in unmatched {
+        let mut old_entries = existing.remove(&key).unwrap_or_default().into_iter();
+        for mut new_entry in new_entries {
+            match old_entries.next() {
+                Some(old_entry) => {
+                    new_entry.id = old_entry.id;
+                    changes.changed.push((old_entry, new_entry));
                 }
-            },
-            None => None,
-        },
-        tense: match sqlite_entry.tense {
-            Some(t) => match t {
-                0 => Some(Tense::Future),
-                1 => Some(Tense::Imperfect),
-                2 => Some(Tense::Past),
-                3 => Some(Tense::Plusquamperfekt),
-                4 => Some(Tense::Present),
-                _ => {
-                    dbg!(t);
-                    panic!("tense not recognised");
+                None => {
+                    let next_id = next_ids.entry(new_entry.word.clone()).or_insert(101);
+                    new_entry.id = *next_id;
+                    *next_id += 1;
+                    changes.added.push(new_entry);
                 }
-            },
-            None => None,
-        },
-        verb_form: sqlite_entry.verb_form.map(|_true_var| VerbForm::Verbnoun),
-    }
-}
-
-pub fn convert_text_to_sqlite(text_entry: LecsiconEntry) -> SqliteLecsiconEntry {
-    SqliteLecsiconEntry {
-        id: text_entry.id,
-        word: text_entry.word,
-        lemma: text_entry.lemma,
-        tag: match text_entry.tag {
-            Some(tag) => match tag {
-                Tag::Adjective => Some(0),
-                Tag::Adposition => Some(1),
-                Tag::Adverb => Some(2),
-                Tag::Conjugation => Some(3),
-                Tag::Determiner => Some(4),
-                Tag::Interjection => Some(5),
-                Tag::Noun => Some(6),
-                Tag::Number => Some(7),
-                Tag::Particle => Some(8),
-                Tag::Pronoun => Some(9),
-                Tag::ProperNoun => Some(10),
-                Tag::Verb => Some(11),
-            },
-            None => None,
-        },
-        adposition_type: text_entry
-            .adposition_type
-            .map(|AdpositionType::Preposition| true),
-        definite: text_entry.definite.map(|Definite::Definite| true),
-        degree: match text_entry.degree {
-            Some(degree) => match degree {
-                Degree::Comparative => Some(0),
-                Degree::Equative => Some(1),
-                Degree::Positive => Some(2),
-                Degree::Superlative => Some(3),
-            },
-            None => None,
-        },
-        gender: match text_entry.gender {
-            Some(gender) => match gender {
-                Gender::Feminine => Some(0),
-                Gender::FeminineMasculine => Some(1),
-                Gender::Masculine => Some(2),
-            },
-            None => None,
-        },
-        mood: match text_entry.mood {
-            Some(mood) => match mood {
-                Mood::Imperative => Some(0),
-                Mood::Indicative => Some(1),
-                Mood::Subjunctive => Some(2),
-            },
-            None => None,
-        },
-        mutation: match text_entry.mutation {
-            Some(mutation) => match mutation {
-                Mutation::Aspirate => Some(0),
-                Mutation::HProthesis => Some(1),
-                Mutation::Nasal => Some(2),
-                Mutation::Soft => Some(3),
-            },
-            None => None,
-        },
-        name_type: match text_entry.name_type {
-            Some(name_type) => match name_type {
-                NameType::Geographical => Some(0),
-                NameType::Person => Some(1),
-            },
-            None => None,
-        },
-        number: match text_entry.number {
-            Some(number) => match number {
-                Number::Collective => Some(0),
-                Number::Plural => Some(1),
-                Number::Singular => Some(2),
-            },
-            None => None,
-        },
-        number_form: text_entry.number_form.map(|NumberForm::Word| true),
-        number_type: match text_entry.number_type {
-            Some(n_t) => match n_t {
-                NumberType::Cardinal => Some(0),
-                NumberType::Ordinal => Some(1),
-            },
-            None => None,
-        },
-        person: match text_entry.person {
-            Some(person) => match person {
-                Person::One => Some(0),
-                Person::Three => Some(1),
-                Person::Two => Some(2),
-                Person::Zero => Some(3),
-            },
-            None => None,
-        },
-        polarity: text_entry.polarity.map(|Polarity::Negative| true),
-        polite: match text_entry.polite {
-            Some(p) => match p {
-                Polite::Formal => Some(0),
-                Polite::Informal => Some(1),
-            },
-            None => None,
-        },
-        position: text_entry.position.map(|Position::Prenom| true),
-        possessive: text_entry.possessive.map(|Possessive::Yes| true),
-        pronoun_type: match text_entry.pronoun_type {
-            Some(p_t) => match p_t {
-                PronounType::Article => Some(0),
-                PronounType::Demonstrative => Some(1),
-                PronounType::Interrogative => Some(2),
-                PronounType::Negative => Some(3),
-                PronounType::Personal => Some(4),
-                PronounType::Reflexive => Some(5),
-                PronounType::Relative => Some(6),
-            },
-            None => None,
-        },
-        relative: text_entry.relative.map(|Relative::Rel| true),
-        style: match text_entry.style {
-            Some(s) => match s {
-                Style::Archaic => Some(0),
-                Style::Colloquial => Some(1),
-                Style::Formal => Some(2),
-            },
-            None => None,
-        },
-        tense: match text_entry.tense {
-            Some(t) => match t {
-                Tense::Future => Some(0),
-                Tense::Imperfect => Some(1),
-                Tense::Past => Some(2),
-                Tense::Plusquamperfekt => Some(3),
-                Tense::Present => Some(4),
-            },
-            None => None,
-        },
-        verb_form: text_entry.verb_form.map(|VerbForm::Verbnoun| true),
+            }
+        }
+        changes.removed.extend(old_entries);
+    }
+    changes.removed.extend(existing.into_values().flatten());
+
+    changes
+        .added
+        .sort_by(|a, b| (&a.word, a.id).cmp(&(&b.word, b.id)));
+    changes
+        .removed
+        .sort_by(|a, b| (&a.word, a.id).cmp(&(&b.word, b.id)));
+    changes
+        .changed
+        .sort_by(|(a, _), (b, _)| (&a.word, a.id).cmp(&(&b.word, b.id)));
+    Ok(changes)
+}
+
+#[cfg(feature = "sqlite")]
+fn apply_changes(
+    conn: &mut SqliteConnection,
+    changes: &mut SqliteChanges,
+) -> Result<(), LecsiconError> {
+    use crate::schema::lecsicon::dsl::*;
+
+    let mut lemma_ids = LemmaIds::default();
+    for entry in &changes.removed {
+        diesel::delete(lecsicon.find((entry.id, &entry.word))).execute(conn)?;
+    }
+    for (old_entry, new_entry) in &mut changes.changed {
+        lemma_ids.resolve(conn, new_entry)?;
+        diesel::update(lecsicon.find((old_entry.id, &old_entry.word)))
+            .set(&*new_entry)
+            .execute(conn)?;
+    }
+    for entry in &changes.moved {
+        diesel::update(lecsicon.find((entry.id, &entry.word)))
+            .set(entry)
+            .execute(conn)?;
+    }
+    for entry in &mut changes.added {
+        lemma_ids.resolve(conn, entry)?;
+    }
+    for batch in changes.added.chunks(INSERT_BATCH_SIZE) {
+        let batch: Vec<NewLecsiconEntry> =
+            batch.iter().cloned().map(NewLecsiconEntry::from).collect();
+        diesel::insert_into(lecsicon).values(&batch).execute(conn)?;
+    }
+    // lemmas whose last entry was removed or changed
+    diesel::delete(
+        crate::schema::lemmas::table.filter(
+            crate::schema::lemmas::id
+                .nullable()
+                .ne_all(lecsicon.select(lemma_id).filter(lemma_id.is_not_null())),
+        ),
+    )
+    .execute(conn)?;
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+impl From<SqliteChanges> for LecsiconChanges {
+    fn from(changes: SqliteChanges) -> LecsiconChanges {
+        LecsiconChanges {
+            added: changes.added,
+            removed: changes.removed,
+            changed: changes.changed,
+            summary: ImportSummary::default(),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub fn compare_data_with_sqlite_db<I>(
+    data: I,
+    db_file_path: &Path,
+    mode: ImportMode,
+) -> Result<LecsiconChanges, LecsiconError>
+where
+    I: IntoIterator<Item = Result<LecsiconEntry, LecsiconError>>,
+{
+    let mut conn = establish_connection(db_file_path)?;
+    let mut summary = ImportSummary::default();
+    let mut rejects: Vec<NewLecsiconReject> = vec![];
+    let changes = diff_with_sqlite_db(
+        screen_entries(data, mode, &mut rejects, &mut summary),
+        &mut conn,
+    )?;
+    let mut changes = LecsiconChanges::from(changes);
+    changes.summary = summary;
+    Ok(changes)
+}
+
+#[cfg(feature = "sqlite")]
+pub fn update_sqlite_db<I>(
+    data: I,
+    db_file_path: &Path,
+    source_path: Option<&Path>,
+    mode: ImportMode,
+) -> Result<LecsiconChanges, LecsiconError>
+where
+    I: IntoIterator<Item = Result<LecsiconEntry, LecsiconError>>,
+{
+    let mut conn = establish_connection(db_file_path)?;
+    let (changes, summary) = conn.immediate_transaction(|conn| {
+        let mut summary = ImportSummary::default();
+        let mut rejects: Vec<NewLecsiconReject> = vec![];
+        let mut changes =
+            diff_with_sqlite_db(screen_entries(data, mode, &mut rejects, &mut summary), conn)?;
+        apply_changes(conn, &mut changes)?;
+        save_rejects(conn, &rejects)?;
+        if let Some(source_path) = source_path {
+            record_import(conn, source_path, &summary)?;
+        }
+        Ok::<(SqliteChanges, ImportSummary), LecsiconError>((changes, summary))
+    })?;
+    let mut changes = LecsiconChanges::from(changes);
+    changes.summary = summary;
+    Ok(changes)
+}
+
+impl Tag {
+    pub fn as_upos(&self) -> &'static str {
+        match self {
+            Tag::Adjective => "ADJ",
+            Tag::Adposition => "ADP",
+            Tag::Adverb => "ADV",
+            Tag::Conjugation => "CONJ",
+            Tag::Determiner => "DET",
+            Tag::Interjection => "INTJ",
+            Tag::Noun => "NOUN",
+            Tag::Number => "NUM",
+            Tag::Particle => "PART",
+            Tag::Pronoun => "PRON",
+            Tag::ProperNoun => "PROPN",
+            Tag::Verb => "VERB",
+        }
+    }
+
+    pub fn from_upos(upos: &str) -> Option<Tag> {
+        match upos {
+            "ADJ" => Some(Tag::Adjective),
+            "ADP" => Some(Tag::Adposition),
+            "ADV" => Some(Tag::Adverb),
+            "CONJ" => Some(Tag::Conjugation),
+            "DET" => Some(Tag::Determiner),
+            "INTJ" => Some(Tag::Interjection),
+            "NOUN" => Some(Tag::Noun),
+            "NUM" => Some(Tag::Number),
+            "PART" => Some(Tag::Particle),
+            "PRON" => Some(Tag::Pronoun),
+            "PROPN" => Some(Tag::ProperNoun),
+            "VERB" => Some(Tag::Verb),
+            _ => None,
+        }
+    }
+}
+
+fn write_csv_line<W: Write>(out: &mut W, entry: &LecsiconEntry) -> Result<(), LecsiconError> {
+    write!(
+        out,
+        "{}\t{}\t{}",
+        entry.word,
+        entry.lemma.as_deref().unwrap_or_default(),
+        entry.tag.as_ref().map_or("", Tag::as_upos)
+    )?;
+    // the feature column is written, even empty, whenever extra columns
+    //  follow it
+    match (entry.csv_features(), &entry.extra_fields) {
+        (features, Some(extra_fields)) => {
+            write!(out, "\t{}\t{extra_fields}", features.unwrap_or_default())?
+        }
+        (Some(features), None) => write!(out, "\t{features}")?,
+        (None, None) => {}
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+pub fn write_entries_as_csv<I, W>(entries: I, out: &mut W) -> Result<usize, LecsiconError>
+where
+    I: IntoIterator<Item = LecsiconEntry>,
+    W: Write,
+{
+    let mut lines_written = 0;
+    for entry in entries {
+        write_csv_line(out, &entry)?;
+        lines_written += 1;
     }
+    Ok(lines_written)
+}
+
+#[cfg(feature = "sqlite")]
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
 }
 
-pub fn create_entry(conn: &mut SqliteConnection, new_entry: &SqliteLecsiconEntry) -> bool {
-    match diesel::insert_into(crate::schema::lecsicon::table)
-        .values(new_entry)
-        .execute(conn)
-    {
-        Ok(_t) => true,
-        Err(_e) => false,
+#[cfg(feature = "sqlite")]
+fn filtered_lecsicon(
+    filter: &ExportFilter,
+) -> diesel::dsl::IntoBoxed<
+    '_,
+    diesel::dsl::LeftJoin<crate::schema::lecsicon::table, crate::schema::lemmas::table>,
+    Sqlite,
+> {
+    use crate::schema::lecsicon::dsl::*;
+
+    let mut query = lecsicon
+        .left_join(crate::schema::lemmas::table)
+        .into_boxed();
+    if let Some(filter_tag) = &filter.tag {
+        query = query.filter(tag.eq(filter_tag));
+    }
+    if let Some(filter_lemma) = &filter.lemma {
+        query = query.filter(crate::schema::lemmas::lemma.eq(filter_lemma));
     }
+    // a range, as in get_words_by_prefix, so that the prefix is matched
+    //  case-sensitively like the autocomplete
+    if let Some(word_prefix) = &filter.word_prefix {
+        query = query
+            .filter(word.ge(word_prefix))
+            .filter(word.lt(format!("{word_prefix}\u{10FFFF}")));
+    }
+    query
 }
 
-pub fn establish_connection(db_filepath: &Path) -> Result<Rc<SqliteConnection>, Box<dyn Error>> {
-    // you can set up a database in RAM for faster access with the commands:
-    // sudo mount -t tmpfs -o size=500M tmpfs path/to/tmp_fs
-    // cp database.db path/to/tmp_fs/database.db
-    if db_filepath.exists() {
-        if let Some(db_file_path_str) = db_filepath.to_str() {
-            return Ok(Rc::new(SqliteConnection::establish(db_file_path_str)?));
+// Writes the database out in the lecsicon CSV format, in the order of the
+//  imported file. Without a filter, lines that a lenient import rejected
+//  are written back in place, so a file with LF line endings, a final
+//  newline and no blank lines comes back byte for byte. Entries that were
+//  not read from a file follow at the end.
+#[cfg(feature = "sqlite")]
+pub fn export_sqlite_db_as_csv<W: Write>(
+    conn: &mut SqliteConnection,
+    out: &mut W,
+    filter: &ExportFilter,
+) -> Result<usize, LecsiconError> {
+    use crate::schema::lecsicon::dsl::*;
+
+    let mut rejected_lines: BTreeMap<i32, String> = if *filter == ExportFilter::default() {
+        get_rejects(conn)?
+            .into_iter()
+            .map(|reject| (reject.line, reject.raw_line))
+            .collect()
+    } else {
+        BTreeMap::new()
+    };
+    let mut lines_written = 0;
+    // one ordered query read row by row, as paging it would sort the table
+    //  again for every page
+    let numbered = filtered_lecsicon(filter)
+        .filter(source_line.is_not_null())
+        .order(source_line)
+        .select(LecsiconEntry::as_select())
+        .load_iter::<LecsiconEntry, DefaultLoadingMode>(conn)?;
+    for entry in numbered {
+        let entry = entry?;
+        let line = entry.source_line.unwrap_or_default();
+        while let Some(rejected) = rejected_lines.first_entry() {
+            if *rejected.key() >= line {
+                break;
+            }
+            writeln!(out, "{}", rejected.remove())?;
+            lines_written += 1;
         }
+        // the entry was imported without some of its features, but its
+        //  raw feature column still holds them
+        rejected_lines.remove(&line);
+        write_csv_line(out, &entry)?;
+        lines_written += 1;
+    }
+    for rejected in rejected_lines.into_values() {
+        writeln!(out, "{rejected}")?;
+        lines_written += 1;
     }
-    Err("unable to establish connection with database".into())
+    let unnumbered: Vec<LecsiconEntry> = filtered_lecsicon(filter)
+        .filter(source_line.is_null())
+        .order((word, id))
+        .select(LecsiconEntry::as_select())
+        .load(conn)?;
+    Ok(lines_written + write_entries_as_csv(unnumbered, out)?)
 }
 
-pub fn get_lecsicon_entries_by_word(
-    word: &str,
+// Calls `f` on every entry matching the filter, in word order, reading
+//  the rows of one ordered query as it goes.
+#[cfg(feature = "sqlite")]
+fn for_each_lecsicon_entry<F>(
     conn: &mut SqliteConnection,
-) -> Vec<SqliteLecsiconEntry> {
-    crate::schema::lecsicon::table
-        .filter(crate::schema::lecsicon::word.eq(word))
-        .load(conn)
-        .expect("get_lecsicon_entries_by_word(): Error searching for entries by word")
+    filter: &ExportFilter,
+    mut f: F,
+) -> Result<(), LecsiconError>
+where
+    F: FnMut(LecsiconEntry) -> Result<(), LecsiconError>,
+{
+    use crate::schema::lecsicon::dsl::*;
+
+    let entries = filtered_lecsicon(filter)
+        .order((word, id))
+        .select(LecsiconEntry::as_select())
+        .load_iter::<LecsiconEntry, DefaultLoadingMode>(conn)?;
+    for entry in entries {
+        f(entry?)?;
+    }
+    Ok(())
 }
 
-pub fn get_related_entries(
-    entry: LecsiconEntry,
-    db_connection: &mut SqliteConnection,
-) -> Option<Vec<LecsiconEntry>> {
-    let sqlite_compatible_entry = convert_text_to_sqlite(entry.clone());
-    let mut entries: Vec<LecsiconEntry> = match entry.tag {
-        Some(Tag::Verb) => {
-            if entry.verb_form.is_some() {
-                // Berfenw
-                crate::schema::lecsicon::table
-                    .filter(crate::schema::lecsicon::lemma.eq(&sqlite_compatible_entry.word))
-                    .filter(crate::schema::lecsicon::tag.eq(&sqlite_compatible_entry.tag))
-                    .load(db_connection)
-                    .expect("get_related_entries(): Error searching for entries")
-                    .into_iter()
-                    .map(convert_sqlite_to_text)
-                    .collect()
-            } else {
-                // Berf yn unig
-                crate::schema::lecsicon::table
-                    .filter(crate::schema::lecsicon::lemma.eq(&sqlite_compatible_entry.word))
-                    .filter(crate::schema::lecsicon::tag.eq(&sqlite_compatible_entry.tag))
-                    .filter(crate::schema::lecsicon::mood.eq(&sqlite_compatible_entry.mood))
-                    .filter(crate::schema::lecsicon::tense.eq(&sqlite_compatible_entry.tense))
-                    .filter(crate::schema::lecsicon::number.eq(&sqlite_compatible_entry.number))
-                    .filter(crate::schema::lecsicon::person.eq(&sqlite_compatible_entry.person))
-                    .load(db_connection)
-                    .expect("get_related_entries(): Error searching for entries")
-                    .into_iter()
-                    .map(convert_sqlite_to_text)
-                    .collect()
-            }
-        }
-        _ => crate::schema::lecsicon::table
-            .filter(crate::schema::lecsicon::lemma.eq(&sqlite_compatible_entry.word))
-            .filter(crate::schema::lecsicon::tag.eq(&sqlite_compatible_entry.tag))
-            .load(db_connection)
-            .expect("get_related_entries(): Error searching for entries")
-            .into_iter()
-            .map(convert_sqlite_to_text)
-            .collect(),
+// Writes one JSON object per line, in word order.
+#[cfg(all(feature = "sqlite", feature = "serde"))]
+pub fn export_sqlite_db_as_jsonl<W: Write>(
+    conn: &mut SqliteConnection,
+    out: &mut W,
+    filter: &ExportFilter,
+) -> Result<usize, LecsiconError> {
+    let mut lines_written = 0;
+    for_each_lecsicon_entry(conn, filter, |entry| {
+        serde_json::to_writer(&mut *out, &entry)?;
+        writeln!(out)?;
+        lines_written += 1;
+        Ok(())
+    })?;
+    Ok(lines_written)
+}
+
+// Writes `lemma\tform\tFEATS` triples in word order, counting every UD
+//  feature that had to be left out.
+#[cfg(feature = "sqlite")]
+pub fn export_sqlite_db_as_unimorph<W: Write>(
+    conn: &mut SqliteConnection,
+    out: &mut W,
+    filter: &ExportFilter,
+) -> Result<UnimorphReport, LecsiconError> {
+    let mut report = UnimorphReport::default();
+    for_each_lecsicon_entry(conn, filter, |entry| {
+        let (features, unmapped) = entry.unimorph_features();
+        writeln!(
+            out,
+            "{}\t{}\t{}",
+            entry.lemma.as_deref().unwrap_or_default(),
+            entry.word,
+            features.join(";")
+        )?;
+        report.entries_written += 1;
+        for feature in unmapped {
+            *report.unmapped.entry(feature).or_default() += 1;
+        }
+        Ok(())
+    })?;
+    Ok(report)
+}
+
+#[cfg(feature = "snapshot")]
+const SNAPSHOT_MAGIC: &[u8; 8] = b"LECSNAP\0";
+#[cfg(feature = "snapshot")]
+const SNAPSHOT_VERSION: u32 = 2;
+// Magic, version, then the byte lengths of the six sections that follow:
+//  word FST, lemma FST, analyses, postings, string offsets and strings.
+#[cfg(feature = "snapshot")]
+const SNAPSHOT_HEADER_LEN: usize = 8 + 4 + 6 * 8;
+
+#[cfg(all(feature = "sqlite", feature = "snapshot"))]
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[cfg(feature = "snapshot")]
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, LecsiconError> {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes.get(*pos).ok_or(LecsiconError::InvalidSnapshot(
+            "record runs past its section",
+        ))?;
+        *pos += 1;
+        if shift > 63 {
+            return Err(LecsiconError::InvalidSnapshot("integer too long"));
+        }
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+// The snapshot's string table. Strings are numbered from 1 so that 0 can
+//  stand for None, and each is stored once however often it is used.
+#[cfg(all(feature = "sqlite", feature = "snapshot"))]
+#[derive(Default)]
+struct SnapshotStrings {
+    index: HashMap<String, u64>,
+    offsets: Vec<u8>,
+    strings: Vec<u8>,
+}
+
+#[cfg(all(feature = "sqlite", feature = "snapshot"))]
+impl SnapshotStrings {
+    fn intern(&mut self, value: Option<&str>) -> u64 {
+        let Some(value) = value else {
+            return 0;
+        };
+        if let Some(number) = self.index.get(value) {
+            return *number;
+        }
+        let number = self.index.len() as u64 + 1;
+        self.strings.extend_from_slice(value.as_bytes());
+        self.offsets
+            .extend_from_slice(&(self.strings.len() as u32).to_le_bytes());
+        self.index.insert(value.to_string(), number);
+        number
+    }
+}
+
+// Packs the analyses of one word, recording the word under each of its
+//  lemmas for `related`.
+#[cfg(all(feature = "sqlite", feature = "snapshot"))]
+fn pack_snapshot_word(
+    group: &mut Vec<LecsiconEntry>,
+    words: &mut fst::MapBuilder<Vec<u8>>,
+    analyses: &mut Vec<u8>,
+    strings: &mut SnapshotStrings,
+    lemma_words: &mut BTreeMap<String, Vec<u64>>,
+) -> Result<(), LecsiconError> {
+    let Some(first) = group.first() else {
+        return Ok(());
     };
+    let word_number = strings.intern(Some(&first.word));
+    words.insert(&first.word, analyses.len() as u64)?;
+    write_varint(analyses, group.len() as u64);
+    for entry in group.iter() {
+        write_varint(analyses, u64::from(entry.id as u32));
+        write_varint(
+            analyses,
+            entry
+                .lemma_id
+                .map_or(0, |lemma_id| u64::from(lemma_id as u32) + 1),
+        );
+        let features = entry.feature_pairs().join("|");
+        for value in [
+            entry.lemma.as_deref(),
+            entry.tag.as_ref().map(Tag::as_upos),
+            Some(features.as_str()).filter(|f| !f.is_empty()),
+            entry.raw_features.as_deref(),
+            entry.extra_fields.as_deref(),
+        ] {
+            write_varint(analyses, strings.intern(value));
+        }
+        write_varint(
+            analyses,
+            entry
+                .source_line
+                .map_or(0, |line| u64::from(line as u32) + 1),
+        );
+        if let Some(entry_lemma) = &entry.lemma {
+            let listed = lemma_words.entry(entry_lemma.clone()).or_default();
+            if listed.last() != Some(&word_number) {
+                listed.push(word_number);
+            }
+        }
+    }
+    group.clear();
+    Ok(())
+}
 
-    if entries.is_empty() {
-        None
-    } else {
-        // filter out the entry that these related entries
-        //  are linked to (don't return itself)
-        entries.retain(|e| *e != entry);
-        Some(entries)
+// Compiles the database into a snapshot for `LecsiconSnapshot`, returning
+//  the number of entries written.
+#[cfg(all(feature = "sqlite", feature = "snapshot"))]
+pub fn write_sqlite_db_as_snapshot<W: Write>(
+    conn: &mut SqliteConnection,
+    out: &mut W,
+) -> Result<usize, LecsiconError> {
+    let mut strings = SnapshotStrings::default();
+    let mut words = fst::MapBuilder::memory();
+    let mut analyses = vec![];
+    let mut lemma_words: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+    let mut group: Vec<LecsiconEntry> = vec![];
+    let mut entries_written = 0;
+    for_each_lecsicon_entry(conn, &ExportFilter::default(), |entry| {
+        if group.first().is_some_and(|first| first.word != entry.word) {
+            pack_snapshot_word(
+                &mut group,
+                &mut words,
+                &mut analyses,
+                &mut strings,
+                &mut lemma_words,
+            )?;
+        }
+        group.push(entry);
+        entries_written += 1;
+        Ok(())
+    })?;
+    pack_snapshot_word(
+        &mut group,
+        &mut words,
+        &mut analyses,
+        &mut strings,
+        &mut lemma_words,
+    )?;
+
+    let mut lemmas = fst::MapBuilder::memory();
+    let mut postings = vec![];
+    for (lemma_text, word_numbers) in lemma_words {
+        lemmas.insert(&lemma_text, postings.len() as u64)?;
+        write_varint(&mut postings, word_numbers.len() as u64);
+        for word_number in word_numbers {
+            write_varint(&mut postings, word_number);
+        }
     }
+
+    let sections = [
+        words.into_inner()?,
+        lemmas.into_inner()?,
+        analyses,
+        postings,
+        strings.offsets,
+        strings.strings,
+    ];
+    out.write_all(SNAPSHOT_MAGIC)?;
+    out.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+    for section in &sections {
+        out.write_all(&(section.len() as u64).to_le_bytes())?;
+    }
+    for section in &sections {
+        out.write_all(section)?;
+    }
+    Ok(entries_written)
 }
 
-pub fn convert_csv_to_sqlite_format(data: Vec<LecsiconEntry>) -> Vec<SqliteLecsiconEntry> {
-    data.into_iter()
-        .map(convert_text_to_sqlite)
-        .collect::<Vec<SqliteLecsiconEntry>>()
+// Whether two optional values are equal the way SQL's `=` sees them, where
+//  NULL equals nothing, so that snapshot lookups match the database's.
+#[cfg(feature = "snapshot")]
+fn sql_eq<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+    a.is_some() && a == b
 }
 
-pub fn save_data_to_sqlite_db(data: Vec<SqliteLecsiconEntry>, db_file_path: &Path) -> bool {
-    if let Ok(mut conn) = establish_connection(db_file_path) {
-        for mut entry in data {
-            if !create_entry(Rc::get_mut(&mut conn).unwrap(), &entry) {
-                let same_words =
-                    get_lecsicon_entries_by_word(&entry.word, Rc::get_mut(&mut conn).unwrap());
-                for e in same_words {
-                    if e.id >= entry.id {
-                        entry.id = e.id + 1;
+#[cfg(feature = "snapshot")]
+impl<'a> LecsiconSnapshot<'a> {
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<LecsiconSnapshot<'a>, LecsiconError> {
+        if bytes.len() < SNAPSHOT_HEADER_LEN || &bytes[..8] != SNAPSHOT_MAGIC {
+            return Err(LecsiconError::InvalidSnapshot("not a lecsicon snapshot"));
+        }
+        if bytes[8..12] != SNAPSHOT_VERSION.to_le_bytes() {
+            return Err(LecsiconError::InvalidSnapshot(
+                "snapshot written by an incompatible version",
+            ));
+        }
+        let mut sections = vec![];
+        let mut start = SNAPSHOT_HEADER_LEN;
+        for length in bytes[12..SNAPSHOT_HEADER_LEN].chunks_exact(8) {
+            let mut length_bytes = [0; 8];
+            length_bytes.copy_from_slice(length);
+            let end = usize::try_from(u64::from_le_bytes(length_bytes))
+                .ok()
+                .and_then(|length| start.checked_add(length))
+                .filter(|end| *end <= bytes.len())
+                .ok_or(LecsiconError::InvalidSnapshot("snapshot is truncated"))?;
+            sections.push(&bytes[start..end]);
+            start = end;
+        }
+        Ok(LecsiconSnapshot {
+            words: fst::Map::new(sections[0])?,
+            lemmas: fst::Map::new(sections[1])?,
+            analyses: sections[2],
+            postings: sections[3],
+            string_offsets: sections[4],
+            strings: sections[5],
+        })
+    }
+
+    fn string(&self, number: u64) -> Result<Option<&'a str>, LecsiconError> {
+        if number == 0 {
+            return Ok(None);
+        }
+        let offset = |n: u64| -> Result<usize, LecsiconError> {
+            if n == 0 {
+                return Ok(0);
+            }
+            let at = usize::try_from(n - 1)
+                .unwrap_or(usize::MAX)
+                .saturating_mul(4);
+            let end = self
+                .string_offsets
+                .get(at..at.saturating_add(4))
+                .ok_or(LecsiconError::InvalidSnapshot("string number out of range"))?;
+            Ok(u32::from_le_bytes([end[0], end[1], end[2], end[3]]) as usize)
+        };
+        let text = self
+            .strings
+            .get(offset(number - 1)?..offset(number)?)
+            .ok_or(LecsiconError::InvalidSnapshot(
+                "string runs past its section",
+            ))?;
+        std::str::from_utf8(text)
+            .map(Some)
+            .map_err(|_| LecsiconError::InvalidSnapshot("string is not UTF-8"))
+    }
+
+    fn entries_at(&self, word: &str, offset: u64) -> Result<Vec<LecsiconEntry>, LecsiconError> {
+        let mut pos = usize::try_from(offset).unwrap_or(usize::MAX);
+        let count = read_varint(self.analyses, &mut pos)?;
+        let mut entries = vec![];
+        for _ in 0..count {
+            let mut entry = LecsiconEntry {
+                word: word.to_string(),
+                ..Default::default()
+            };
+            entry.id = read_varint(self.analyses, &mut pos)? as u32 as i32;
+            entry.lemma_id = match read_varint(self.analyses, &mut pos)? {
+                0 => None,
+                lemma_id => Some((lemma_id - 1) as u32 as i32),
+            };
+            let mut next_string = || -> Result<Option<&'a str>, LecsiconError> {
+                let number = read_varint(self.analyses, &mut pos)?;
+                self.string(number)
+            };
+            entry.lemma = next_string()?.map(str::to_string);
+            if let Some(upos) = next_string()? {
+                entry.tag = Some(
+                    Tag::from_upos(upos)
+                        .ok_or(LecsiconError::InvalidSnapshot("unknown part of speech"))?,
+                );
+            }
+            if let Some(features) = next_string()? {
+                for tag_pair in features.split('|') {
+                    if !entry.add_feature(tag_pair) {
+                        return Err(LecsiconError::InvalidSnapshot("malformed feature"));
                     }
                 }
-                if !create_entry(Rc::get_mut(&mut conn).unwrap(), &entry) {
-                    dbg!(entry);
-                    panic!("re-attempt failed");
+            }
+            entry.raw_features = next_string()?.map(str::to_string);
+            entry.extra_fields = next_string()?.map(str::to_string);
+            entry.source_line = match read_varint(self.analyses, &mut pos)? {
+                0 => None,
+                line => Some((line - 1) as u32 as i32),
+            };
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+
+    // Every analysis of the word, in id order, as `search` gives them.
+    pub fn search(&self, word: &str) -> Result<Vec<LecsiconEntry>, LecsiconError> {
+        match self.words.get(word) {
+            Some(offset) => self.entries_at(word, offset),
+            None => Err(LecsiconError::NotFound(word.to_string())),
+        }
+    }
+
+    // Words starting with the prefix, in byte order and case-sensitively,
+    //  as `suggest` gives them from the database.
+    pub fn suggest(&self, prefix: &str) -> Result<Vec<String>, LecsiconError> {
+        let matcher = fst::automaton::Str::new(prefix).starts_with();
+        let mut stream = self.words.search(matcher).into_stream();
+        let mut suggestions = vec![];
+        while let Some((key, _)) = stream.next() {
+            suggestions.push(
+                String::from_utf8(key.to_vec())
+                    .map_err(|_| LecsiconError::InvalidSnapshot("word is not UTF-8"))?,
+            );
+        }
+        Ok(suggestions)
+    }
+
+    // The same entries as `related`, ordered by word and id.
+    pub fn related(&self, entry: &LecsiconEntry) -> Result<Vec<LecsiconEntry>, LecsiconError> {
+        let mut entries = vec![];
+        if let Some(offset) = self.lemmas.get(&entry.word) {
+            let mut pos = usize::try_from(offset).unwrap_or(usize::MAX);
+            let count = read_varint(self.postings, &mut pos)?;
+            for _ in 0..count {
+                let word_number = read_varint(self.postings, &mut pos)?;
+                let form = self
+                    .string(word_number)?
+                    .ok_or(LecsiconError::InvalidSnapshot("posting without a word"))?;
+                entries.extend(self.search(form)?.into_iter().filter(|e| {
+                    e.lemma.as_deref() == Some(entry.word.as_str())
+                        && sql_eq(&e.tag, &entry.tag)
+                        && (entry.tag != Some(Tag::Verb)
+                            || entry.verb_form.is_some()
+                            || (sql_eq(&e.mood, &entry.mood)
+                                && sql_eq(&e.tense, &entry.tense)
+                                && sql_eq(&e.number, &entry.number)
+                                && sql_eq(&e.person, &entry.person)))
+                }));
+            }
+        }
+        entries.retain(|e| e != entry);
+        if entries.is_empty() {
+            Err(LecsiconError::NotFound(entry.word.clone()))
+        } else {
+            Ok(entries)
+        }
+    }
+}
+
+// A Hunspell prefix rule as (strip, add, condition).
+#[cfg(feature = "sqlite")]
+type AffixRule = (&'static str, &'static str, &'static str);
+
+// Welsh initial mutations as Hunspell prefix classes. The conditions keep
+//  the digraphs ch, ph, th and dd from being read as c, p, t and d.
+#[cfg(feature = "sqlite")]
+const HUNSPELL_MUTATIONS: [(char, &[AffixRule]); 4] = [
+    (
+        'S',
+        &[
+            ("p", "b", "p[^h]"),
+            ("t", "d", "t[^h]"),
+            ("c", "g", "c[^h]"),
+            ("b", "f", "b"),
+            ("d", "dd", "d[^d]"),
+            ("g", "", "g"),
+            ("m", "f", "m"),
+            ("ll", "l", "ll"),
+            ("rh", "r", "rh"),
+        ],
+    ),
+    (
+        'N',
+        &[
+            ("p", "mh", "p[^h]"),
+            ("t", "nh", "t[^h]"),
+            ("c", "ngh", "c[^h]"),
+            ("b", "m", "b"),
+            ("d", "n", "d[^d]"),
+            ("g", "ng", "g"),
+        ],
+    ),
+    (
+        'A',
+        &[
+            ("p", "ph", "p[^h]"),
+            ("t", "th", "t[^h]"),
+            ("c", "ch", "c[^h]"),
+        ],
+    ),
+    ('H', &[("", "h", "[aâäáàeêëéèiîïíìoôöóòuûüúùwŵẅẃẁyŷÿýỳ]")]),
+];
+
+// Matches the start of a word against a Hunspell affix condition, which
+//  here is only ever literal characters and [...] or [^...] classes.
+#[cfg(feature = "sqlite")]
+fn hunspell_condition_matches(condition: &str, word: &str) -> bool {
+    let mut letters = word.chars();
+    let mut pattern = condition.chars();
+    while let Some(expected) = pattern.next() {
+        let Some(letter) = letters.next() else {
+            return false;
+        };
+        if expected == '[' {
+            let mut negated = false;
+            let mut class = vec![];
+            for member in pattern.by_ref() {
+                match member {
+                    ']' => break,
+                    '^' if class.is_empty() && !negated => negated = true,
+                    _ => class.push(member),
                 }
             }
+            if class.contains(&letter) == negated {
+                return false;
+            }
+        } else if expected != letter {
+            return false;
+        }
+    }
+    true
+}
+
+// The forms a mutation class generates from a word.
+#[cfg(feature = "sqlite")]
+fn mutated_forms(rules: &[AffixRule], word: &str) -> Vec<String> {
+    rules
+        .iter()
+        .filter(|(_, _, condition)| hunspell_condition_matches(condition, word))
+        .map(|(strip, add, _)| format!("{add}{}", &word[strip.len()..]))
+        .collect()
+}
+
+// Writes a Hunspell dictionary of every word in the database. A word is
+//  given a mutation flag only when all the forms the flag generates are
+//  in the lexicon too, so the dictionary accepts exactly the lexicon's
+//  words, and forms covered that way are left out of the .dic file.
+#[cfg(feature = "sqlite")]
+pub fn export_sqlite_db_as_hunspell<D: Write, A: Write>(
+    conn: &mut SqliteConnection,
+    dic: &mut D,
+    aff: &mut A,
+) -> Result<HunspellReport, LecsiconError> {
+    use crate::schema::lecsicon::dsl::*;
+
+    let mut report = HunspellReport::default();
+    let words: Vec<String> = lecsicon.select(word).distinct().order(word).load(conn)?;
+    let (words, skipped): (Vec<String>, Vec<String>) = words
+        .into_iter()
+        .partition(|w| !w.is_empty() && !w.contains(char::is_whitespace));
+    report.words_skipped = skipped.len();
+    let known: std::collections::HashSet<&str> = words.iter().map(String::as_str).collect();
+
+    let mut flags: Vec<String> = vec![String::new(); words.len()];
+    let mut covered: std::collections::HashSet<String> = std::collections::HashSet::new();
+    for (w, word_flags) in words.iter().zip(flags.iter_mut()) {
+        for (flag, rules) in HUNSPELL_MUTATIONS.iter() {
+            let forms = mutated_forms(rules, w);
+            if !forms.is_empty() && forms.iter().all(|form| known.contains(form.as_str())) {
+                word_flags.push(*flag);
+                covered.extend(forms);
+            }
+        }
+    }
+
+    // a covered word still needs its own line if it carries flags
+    let stems: Vec<(&String, &String)> = words
+        .iter()
+        .zip(flags.iter())
+        .filter(|(w, word_flags)| !word_flags.is_empty() || !covered.contains(w.as_str()))
+        .collect();
+    report.stems_written = stems.len();
+    report.flagged_stems = stems.iter().filter(|(_, f)| !f.is_empty()).count();
+    report.forms_covered = words.len() - stems.len();
+
+    writeln!(dic, "{}", stems.len())?;
+    for (w, word_flags) in stems {
+        let escaped = w.replace('/', "\\/");
+        if word_flags.is_empty() {
+            writeln!(dic, "{escaped}")?;
+        } else {
+            writeln!(dic, "{escaped}/{word_flags}")?;
+        }
+    }
+
+    // TRY lists letters most frequent first for the suggestion engine, and
+    //  WORDCHARS any other characters that appear inside words
+    let mut letter_counts: BTreeMap<char, usize> = BTreeMap::new();
+    for w in &words {
+        for letter in w.chars() {
+            *letter_counts.entry(letter).or_default() += 1;
+        }
+    }
+    let mut letters: Vec<(char, usize)> = letter_counts.into_iter().collect();
+    letters.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let try_letters: String = letters
+        .iter()
+        .map(|(letter, _)| *letter)
+        .filter(|letter| letter.is_alphabetic())
+        .collect();
+    let word_chars: String = letters
+        .iter()
+        .map(|(letter, _)| *letter)
+        .filter(|letter| !letter.is_alphabetic())
+        .collect();
+
+    writeln!(aff, "SET UTF-8")?;
+    writeln!(aff, "TRY {try_letters}")?;
+    if !word_chars.is_empty() {
+        writeln!(aff, "WORDCHARS {word_chars}")?;
+    }
+    for (flag, rules) in HUNSPELL_MUTATIONS.iter() {
+        writeln!(aff)?;
+        writeln!(aff, "PFX {flag} Y {}", rules.len())?;
+        for (strip, add, condition) in rules.iter() {
+            let strip = if strip.is_empty() { "0" } else { strip };
+            let add = if add.is_empty() { "0" } else { add };
+            writeln!(aff, "PFX {flag} {strip} {add} {condition}")?;
         }
-        return true;
     }
-    false
+    Ok(report)
 }
 
+#[cfg(feature = "sqlite")]
 impl SqliteSearcher {
-    pub fn new(connection: Rc<SqliteConnection>, recent_searches: Vec<String>) -> SqliteSearcher {
+    pub fn new(lexicon: Lexicon, recent_searches: Vec<String>) -> SqliteSearcher {
         SqliteSearcher {
             input: "".to_string(),
             recent_searches,
-            connection,
+            lexicon,
             commands: vec![],
+            hunspell_aff: "/usr/share/hunspell/cy_GB.aff".into(),
+            hunspell_dic: "/usr/share/hunspell/cy_GB.dic".into(),
         }
     }
+
+    // Points spelling suggestions at another dictionary, such as one
+    //  written by `export_sqlite_db_as_hunspell`.
+    pub fn with_hunspell(mut self, aff: &Path, dic: &Path) -> SqliteSearcher {
+        self.hunspell_aff = aff.to_path_buf();
+        self.hunspell_dic = dic.to_path_buf();
+        self
+    }
 }
 
+#[cfg(feature = "sqlite")]
 impl Autocomplete for SqliteSearcher {
     fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
         if input.starts_with('/') {
-            return Ok(self
+            Ok(self
                 .commands
                 .clone()
                 .iter()
                 .filter(|s| s.starts_with(input))
                 .map(|s| s.to_string())
-                .collect::<Vec<String>>());
+                .collect::<Vec<String>>())
         } else if input.len() > 2 {
-            let mut results: Vec<String> = crate::schema::lecsicon::table
-                .filter(crate::schema::lecsicon::word.like(format!("{}%", input)))
-                .load(Rc::get_mut(&mut self.connection).unwrap())
-                .expect("get_suggestions(): Error searching for entries by word")
-                .iter()
-                .map(|entry: &SqliteLecsiconEntry| String::from(&entry.word))
-                .collect();
+            let mut conn = self.lexicon.connection()?;
+            let mut results = get_words_by_prefix(input, &mut conn)?;
+            if results.is_empty() {
+                results = get_words_by_folded_prefix(input, &mut conn)?;
+            }
             if results.is_empty() {
                 let c_dic = Hunspell::new(
-                    "/usr/share/hunspell/cy_GB.aff",
-                    "/usr/share/hunspell/cy_GB.dic",
+                    &self.hunspell_aff.to_string_lossy(),
+                    &self.hunspell_dic.to_string_lossy(),
                 );
                 results = c_dic.suggest(input);
-            } else {
-                results.sort_unstable();
-                results.dedup();
             }
             Ok(results)
         } else if input.is_empty() {
-            return Ok(self.recent_searches.to_owned());
+            Ok(self.recent_searches.to_owned())
         } else {
-            return Ok(self
+            Ok(self
                 .recent_searches
                 .iter()
                 .filter(|w| w.starts_with(input))
                 .cloned()
-                .collect::<Vec<String>>());
+                .collect::<Vec<String>>())
         }
     }
 
@@ -756,3 +2681,828 @@ impl Autocomplete for SqliteSearcher {
         })
     }
 }
+
+impl fmt::Display for LecsiconError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LecsiconError::Io(e) => write!(f, "unable to read or write lecsicon file: {e}"),
+            LecsiconError::Csv(e) => write!(f, "unable to parse lecsicon record: {e}"),
+            LecsiconError::MissingField { line, column, .. } => {
+                write!(f, "line {line}: missing column {column}")
+            }
+            LecsiconError::UnrecognisedTag {
+                line, column, raw, ..
+            } => {
+                write!(
+                    f,
+                    "line {line}, column {column}: tag not recognised: <{raw}>"
+                )
+            }
+            LecsiconError::UnrecognisedFeature {
+                line, column, raw, ..
+            } => write!(
+                f,
+                "line {line}, column {column}: unrecognised tag pair: <{raw}>"
+            ),
+            LecsiconError::MissingDatabase(path) => {
+                write!(f, "no database found at {}", path.display())
+            }
+            #[cfg(feature = "sqlite")]
+            LecsiconError::Connection(e) => {
+                write!(f, "unable to establish connection with database: {e}")
+            }
+            LecsiconError::Backup(message) => {
+                write!(f, "unable to copy database into memory: {message}")
+            }
+            LecsiconError::ReadOnly => {
+                write!(f, "database was opened read-only and cannot be changed")
+            }
+            LecsiconError::InvalidOpenMode(mode) => {
+                write!(f, "a new in-memory database cannot be opened {mode:?}")
+            }
+            LecsiconError::OutdatedSchema(version) => write!(
+                f,
+                "database lacks migration {version}; open it read-write once to update it"
+            ),
+            #[cfg(feature = "sqlite")]
+            LecsiconError::Pool(e) => write!(f, "unable to get a database connection: {e}"),
+            LecsiconError::Migration(e) => write!(f, "unable to migrate database: {e}"),
+            LecsiconError::UnsupportedSchema(version) => write!(
+                f,
+                "database has migration {version}, which this version of lecsicon-db does not know"
+            ),
+            #[cfg(feature = "sqlite")]
+            LecsiconError::Database(e) => write!(f, "database error: {e}"),
+            #[cfg(feature = "serde")]
+            LecsiconError::Json(e) => write!(f, "unable to write entries as JSON: {e}"),
+            #[cfg(feature = "snapshot")]
+            LecsiconError::Fst(e) => write!(f, "snapshot FST error: {e}"),
+            #[cfg(feature = "snapshot")]
+            LecsiconError::InvalidSnapshot(reason) => write!(f, "invalid snapshot: {reason}"),
+            LecsiconError::NotFound(word) => write!(f, "no entries found for <{word}>"),
+            LecsiconError::UndecodableRow { column, value } => {
+                write!(f, "{column} value {value} not recognised")
+            }
+        }
+    }
+}
+
+impl Error for LecsiconError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LecsiconError::Io(e) => Some(e),
+            LecsiconError::Csv(e) => Some(e),
+            #[cfg(feature = "sqlite")]
+            LecsiconError::Connection(e) => Some(e),
+            #[cfg(feature = "sqlite")]
+            LecsiconError::Database(e) => Some(e),
+            #[cfg(feature = "sqlite")]
+            LecsiconError::Pool(e) => Some(e),
+            LecsiconError::Migration(e) => Some(e.as_ref()),
+            #[cfg(feature = "serde")]
+            LecsiconError::Json(e) => Some(e),
+            #[cfg(feature = "snapshot")]
+            LecsiconError::Fst(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LecsiconError {
+    fn from(e: std::io::Error) -> LecsiconError {
+        LecsiconError::Io(e)
+    }
+}
+
+impl From<csv::Error> for LecsiconError {
+    fn from(e: csv::Error) -> LecsiconError {
+        LecsiconError::Csv(e)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<diesel::ConnectionError> for LecsiconError {
+    fn from(e: diesel::ConnectionError) -> LecsiconError {
+        LecsiconError::Connection(e)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<diesel::r2d2::PoolError> for LecsiconError {
+    fn from(e: diesel::r2d2::PoolError) -> LecsiconError {
+        LecsiconError::Pool(e)
+    }
+}
+
+// SQLITE_READONLY and its extended result codes.
+#[cfg(feature = "sqlite")]
+const SQLITE_READONLY_CODES: [c_int; 7] = [
+    ffi::SQLITE_READONLY,
+    ffi::SQLITE_READONLY_RECOVERY,
+    ffi::SQLITE_READONLY_CANTLOCK,
+    ffi::SQLITE_READONLY_ROLLBACK,
+    ffi::SQLITE_READONLY_DBMOVED,
+    ffi::SQLITE_READONLY_CANTINIT,
+    ffi::SQLITE_READONLY_DIRECTORY,
+];
+
+// The result code among `codes` that an error from Diesel was raised with.
+//  Diesel keeps only sqlite3_errmsg of a failed call, which for these codes
+//  is the text SQLite gives the code itself, so it is looked up by that.
+#[cfg(feature = "sqlite")]
+fn sqlite_result_code(message: &str, codes: &[c_int]) -> Option<c_int> {
+    codes.iter().copied().find(|&code| {
+        // SAFETY: sqlite3_errstr returns a static, NUL-terminated string for
+        //  any code.
+        let text = unsafe { CStr::from_ptr(ffi::sqlite3_errstr(code)) };
+        text.to_bytes() == message.as_bytes()
+    })
+}
+
+#[cfg(feature = "sqlite")]
+impl From<diesel::result::Error> for LecsiconError {
+    fn from(e: diesel::result::Error) -> LecsiconError {
+        // Diesel passes SQLITE_READONLY on as an unclassified error, and
+        //  feature codes this version cannot decode come back wrapped in the
+        //  field's deserialization error
+        match &e {
+            diesel::result::Error::DatabaseError(_, info)
+                if sqlite_result_code(info.message(), &SQLITE_READONLY_CODES).is_some() =>
+            {
+                LecsiconError::ReadOnly
+            }
+            diesel::result::Error::DeserializationError(inner) => {
+                let mut cause: Option<&(dyn Error + 'static)> = Some(inner.as_ref());
+                while let Some(current) = cause {
+                    if let Some(LecsiconError::UndecodableRow { column, value }) =
+                        current.downcast_ref::<LecsiconError>()
+                    {
+                        return LecsiconError::UndecodableRow {
+                            column,
+                            value: *value,
+                        };
+                    }
+                    cause = current.source();
+                }
+                LecsiconError::Database(e)
+            }
+            _ => LecsiconError::Database(e),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for LecsiconError {
+    fn from(e: serde_json::Error) -> LecsiconError {
+        LecsiconError::Json(e)
+    }
+}
+
+#[cfg(feature = "snapshot")]
+impl From<fst::Error> for LecsiconError {
+    fn from(e: fst::Error) -> LecsiconError {
+        LecsiconError::Fst(e)
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    // Features out of their usual order, an empty feature column, extra
+    //  columns, a doubled separator, a feature and a tag lenient imports
+    //  reject and a line too short to import.
+    const FIXTURE: &str = "\
+meddwl\tmeddwl\tVERB\tVerbForm=Vnoun
+meddyliais\tmeddwl\tVERB\tTense=Past|Mood=Ind|Person=2|Number=Sing
+feddwl\tmeddwl\tVERB\tMutation=SM|VerbForm=Vnoun
+iawn\tiawn\tADV\t
+iawn\tiawn\tADJ\tDegree=Pos
+yr\tyr\tDET\tPronType=Art|Definite=Def
+cath\tcath\tNOUN\tNumber=Sing|Gender=Fem
+cathod\tcath\tNOUN\tGender=Fem|Number=Plur
+gath\tcath\tNOUN\tGender=Fem|Mutation=SM|Number=Sing
+nghath\tcath\tNOUN\tGender=Fem|Mutation=NM|Number=Sing
+chath\tcath\tNOUN\tGender=Fem|Mutation=AM|Number=Sing
+dŵr\tdŵr\tNOUN\tGender=Masc|Number=Sing
+ddŵr\tdŵr\tNOUN\tGender=Masc|Mutation=SM|Number=Sing
+Caerdydd\tCaerdydd\tPROPN\tNameType=Geo
+fi\tfi\tPRON\tNumber=Sing|Person=1|PronType=Prs
+mawr\tmawr\tADJ\tDegree=Pos
+mwy\tmawr\tADJ\tDegree=Cmp
+iawn\tiawn\tNOUN\tGender=Masc|Number=Sing
+foo\tfoo\tSYM\t
+bar\tbar\tNOUN\tForeign=Yes|Number=Sing
+baz
+a\"b\t\"q\"\tNOUN\tNumber=Sing||Gender=Masc\textra1\t
+c\tc\tADV
+d\td\tADJ\t\tx
+";
+
+    fn import(fixture: &str, mode: ImportMode) -> SqliteConnection {
+        let mut conn = establish_connection(Path::new(MEMORY_DB)).unwrap();
+        save_data_to_connection(
+            LecsiconReader::new(fixture.as_bytes()),
+            &mut conn,
+            None,
+            mode,
+        )
+        .unwrap();
+        conn
+    }
+
+    fn export(conn: &mut SqliteConnection, filter: &ExportFilter) -> String {
+        let mut out = vec![];
+        export_sqlite_db_as_csv(conn, &mut out, filter).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn lenient_import_exports_byte_identical() {
+        let mut conn = import(FIXTURE, ImportMode::Lenient);
+        assert_eq!(export(&mut conn, &ExportFilter::default()), FIXTURE);
+    }
+
+    #[test]
+    fn strict_import_exports_byte_identical() {
+        let fixture: String = FIXTURE
+            .lines()
+            .filter(|line| !["foo", "bar", "baz"].contains(&line.split('\t').next().unwrap()))
+            .map(|line| format!("{line}\n"))
+            .collect();
+        let mut conn = import(&fixture, ImportMode::Strict);
+        assert_eq!(export(&mut conn, &ExportFilter::default()), fixture);
+    }
+
+    #[test]
+    fn extra_columns_stay_out_of_the_feature_column() {
+        let entries = vec![
+            LecsiconEntry {
+                word: "d".to_string(),
+                lemma: Some("d".to_string()),
+                tag: Some(Tag::Adjective),
+                extra_fields: Some("x\ty".to_string()),
+                ..Default::default()
+            },
+            LecsiconEntry {
+                word: "cath".to_string(),
+                lemma: Some("cath".to_string()),
+                tag: Some(Tag::Noun),
+                number: Some(Number::Singular),
+                extra_fields: Some("x".to_string()),
+                ..Default::default()
+            },
+        ];
+        let mut out = vec![];
+        write_entries_as_csv(entries.clone(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out.clone()).unwrap(),
+            "d\td\tADJ\t\tx\ty\ncath\tcath\tNOUN\tNumber=Sing\tx\n"
+        );
+        let read: Vec<LecsiconEntry> = LecsiconReader::new(&out[..]).map(Result::unwrap).collect();
+        assert_eq!(read.len(), entries.len());
+        for (read, written) in read.iter().zip(&entries) {
+            assert_eq!(analysis_key(read), analysis_key(written));
+            assert_eq!(read.extra_fields, written.extra_fields);
+        }
+    }
+
+    #[test]
+    fn entries_come_in_word_and_id_order() {
+        // homographs far apart in the file, and more rows than an export
+        //  once read in a page
+        let mut fixture = String::new();
+        for upos in ["NOUN", "ADJ", "VERB"] {
+            for n in (0..4_001).rev() {
+                fixture.push_str(&format!("w{n}\tw{n}\t{upos}\t\n"));
+            }
+        }
+        let mut conn = import(&fixture, ImportMode::Strict);
+        let mut keys = vec![];
+        for_each_lecsicon_entry(&mut conn, &ExportFilter::default(), |entry| {
+            keys.push((entry.word, entry.id));
+            Ok(())
+        })
+        .unwrap();
+        let mut expected: Vec<(String, i32)> = (0..4_001)
+            .flat_map(|n| [101, 102, 103].map(|id| (format!("w{n}"), id)))
+            .collect();
+        expected.sort();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn unimorph_export_reports_unmapped_features() {
+        let mut conn = import(FIXTURE, ImportMode::Lenient);
+        let mut out = vec![];
+        let report =
+            export_sqlite_db_as_unimorph(&mut conn, &mut out, &ExportFilter::default()).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\
+Caerdydd\tCaerdydd\tPROPN
+\"q\"\ta\"b\tN;MASC;SG
+bar\tbar\tN;SG
+c\tc\tADV
+cath\tcath\tN;FEM;SG
+cath\tcathod\tN;FEM;PL
+cath\tchath\tN;FEM;SG
+d\td\tADJ
+dŵr\tddŵr\tN;MASC;SG
+dŵr\tdŵr\tN;MASC;SG
+meddwl\tfeddwl\tV.MSDR
+fi\tfi\tPRO;SG;1
+cath\tgath\tN;FEM;SG
+iawn\tiawn\tADV
+iawn\tiawn\tADJ
+iawn\tiawn\tN;MASC;SG
+mawr\tmawr\tADJ
+meddwl\tmeddwl\tV.MSDR
+meddwl\tmeddyliais\tV;IND;SG;2;PST
+mawr\tmwy\tADJ;CMPR
+cath\tnghath\tN;FEM;SG
+yr\tyr\tDET;DEF
+"
+        );
+        assert_eq!(report.entries_written, 22);
+        let unmapped: Vec<(&str, usize)> = report
+            .unmapped
+            .iter()
+            .map(|(feature, count)| (feature.as_str(), *count))
+            .collect();
+        assert_eq!(
+            unmapped,
+            [
+                ("Degree=Pos", 2),
+                ("Foreign=Yes", 1),
+                ("Mutation=AM", 1),
+                ("Mutation=NM", 1),
+                ("Mutation=SM", 3),
+                ("NameType=Geo", 1),
+                ("PronType=Art", 1),
+                ("PronType=Prs", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn hunspell_export_flags_fully_mutated_stems() {
+        let fixture: String = [
+            "cath", "gath", "nghath", "chath", "tad", "dad", "afal", "hafal", "a/b", "ar y",
+        ]
+        .iter()
+        .map(|w| format!("{w}\t{w}\tNOUN\t\n"))
+        .collect();
+        let mut conn = import(&fixture, ImportMode::Strict);
+        let (mut dic, mut aff) = (vec![], vec![]);
+        let report = export_sqlite_db_as_hunspell(&mut conn, &mut dic, &mut aff).unwrap();
+        assert_eq!(
+            report,
+            HunspellReport {
+                stems_written: 4,
+                flagged_stems: 3,
+                forms_covered: 5,
+                words_skipped: 1,
+            }
+        );
+        // tad has only its soft mutation in the lexicon, so nhad and thad
+        //  are not generated
+        assert_eq!(
+            String::from_utf8(dic).unwrap(),
+            "4\na\\/b\nafal/H\ncath/SNA\ntad/S\n"
+        );
+        assert_eq!(
+            String::from_utf8(aff).unwrap(),
+            "\
+SET UTF-8
+TRY ahtdcfglbn
+WORDCHARS /
+
+PFX S Y 9
+PFX S p b p[^h]
+PFX S t d t[^h]
+PFX S c g c[^h]
+PFX S b f b
+PFX S d dd d[^d]
+PFX S g 0 g
+PFX S m f m
+PFX S ll l ll
+PFX S rh r rh
+
+PFX N Y 6
+PFX N p mh p[^h]
+PFX N t nh t[^h]
+PFX N c ngh c[^h]
+PFX N b m b
+PFX N d n d[^d]
+PFX N g ng g
+
+PFX A Y 3
+PFX A p ph p[^h]
+PFX A t th t[^h]
+PFX A c ch c[^h]
+
+PFX H Y 1
+PFX H 0 h [aâäáàeêëéèiîïíìoôöóòuûüúùwŵẅẃẁyŷÿýỳ]
+"
+        );
+    }
+
+    #[test]
+    fn filtered_export_keeps_matching_lines() {
+        let mut conn = import(FIXTURE, ImportMode::Lenient);
+        let expected = |keep: &dyn Fn(&[&str]) -> bool| -> String {
+            FIXTURE
+                .lines()
+                .filter(|line| {
+                    let fields: Vec<&str> = line.split('\t').collect();
+                    // lines a lenient import rejected are left out
+                    fields.len() > 2 && fields[2] != "SYM" && keep(&fields)
+                })
+                .map(|line| format!("{line}\n"))
+                .collect()
+        };
+        let by_tag = ExportFilter {
+            tag: Some(Tag::Noun),
+            ..Default::default()
+        };
+        assert_eq!(export(&mut conn, &by_tag), expected(&|f| f[2] == "NOUN"));
+        let by_lemma = ExportFilter {
+            lemma: Some("meddwl".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            export(&mut conn, &by_lemma),
+            expected(&|f| f[1] == "meddwl")
+        );
+        let by_prefix = ExportFilter {
+            word_prefix: Some("ca".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            export(&mut conn, &by_prefix),
+            expected(&|f| f[0].starts_with("ca"))
+        );
+    }
+
+    // Against UPDATE_BASE: gath and an iawn noun are added, cathod and the
+    //  iawn adjective removed, mwy given its own lemma, cath moved down a
+    //  line by gath, and ddŵr, mawr and the iawn adverb left as they were.
+    const UPDATE_BASE: &str = "\
+cath\tcath\tNOUN\tNumber=Sing|Gender=Fem
+cathod\tcath\tNOUN\tGender=Fem|Number=Plur
+mawr\tmawr\tADJ\tDegree=Pos
+mwy\tmawr\tADJ\tDegree=Cmp
+iawn\tiawn\tADV\t
+iawn\tiawn\tADJ\tDegree=Pos
+ddŵr\tdŵr\tNOUN\tGender=Masc|Mutation=SM|Number=Sing
+";
+    const UPDATE: &str = "\
+gath\tcath\tNOUN\tGender=Fem|Mutation=SM|Number=Sing
+cath\tcath\tNOUN\tNumber=Sing|Gender=Fem
+mawr\tmawr\tADJ\tDegree=Pos
+mwy\tmwy\tADJ\tDegree=Cmp
+iawn\tiawn\tADV\t
+iawn\tiawn\tNOUN\tGender=Masc|Number=Sing
+ddŵr\tdŵr\tNOUN\tGender=Masc|Mutation=SM|Number=Sing
+";
+
+    fn all_entries(conn: &mut SqliteConnection) -> Vec<LecsiconEntry> {
+        crate::schema::lecsicon::table
+            .left_join(crate::schema::lemmas::table)
+            .order((crate::schema::lecsicon::word, crate::schema::lecsicon::id))
+            .select(LecsiconEntry::as_select())
+            .load(conn)
+            .unwrap()
+    }
+
+    fn word_ids(entries: &[LecsiconEntry]) -> Vec<(&str, i32)> {
+        entries.iter().map(|e| (e.word.as_str(), e.id)).collect()
+    }
+
+    #[test]
+    fn diff_finds_added_removed_changed_and_moved_entries() {
+        let mut conn = import(UPDATE_BASE, ImportMode::Strict);
+        let before = all_entries(&mut conn);
+        let mut changes =
+            diff_with_sqlite_db(LecsiconReader::new(UPDATE.as_bytes()), &mut conn).unwrap();
+
+        assert_eq!(word_ids(&changes.added), [("gath", 101), ("iawn", 103)]);
+        assert_eq!(changes.added[1].tag, Some(Tag::Noun));
+        assert_eq!(word_ids(&changes.removed), [("cathod", 101), ("iawn", 102)]);
+        assert_eq!(changes.changed.len(), 1);
+        let (old_entry, new_entry) = &changes.changed[0];
+        assert_eq!((old_entry.id, new_entry.id), (101, 101));
+        assert_eq!(old_entry.lemma.as_deref(), Some("mawr"));
+        assert_eq!(new_entry.lemma.as_deref(), Some("mwy"));
+        assert_eq!(word_ids(&changes.moved), [("cath", 101)]);
+        assert_eq!(changes.moved[0].source_line, Some(2));
+
+        conn.immediate_transaction(|conn| apply_changes(conn, &mut changes))
+            .unwrap();
+        let after = all_entries(&mut conn);
+        // rows left in place keep their ids
+        for kept in ["cath", "ddŵr", "mawr", "mwy"] {
+            let id =
+                |entries: &[LecsiconEntry]| entries.iter().find(|e| e.word == kept).map(|e| e.id);
+            assert_eq!(id(&before), id(&after), "{kept}");
+        }
+        assert_eq!(
+            word_ids(&after),
+            [
+                ("cath", 101),
+                ("ddŵr", 101),
+                ("gath", 101),
+                ("iawn", 101),
+                ("iawn", 103),
+                ("mawr", 101),
+                ("mwy", 101),
+            ]
+        );
+        assert_eq!(export(&mut conn, &ExportFilter::default()), UPDATE);
+    }
+
+    #[test]
+    fn update_keeps_trigrams_and_search_keys_in_step() {
+        let db_path =
+            std::env::temp_dir().join(format!("lecsicon-update-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&db_path);
+        save_data_to_sqlite_db(
+            LecsiconReader::new(UPDATE_BASE.as_bytes()),
+            &db_path,
+            None,
+            ImportMode::Strict,
+        )
+        .unwrap();
+        update_sqlite_db(
+            LecsiconReader::new(UPDATE.as_bytes()),
+            &db_path,
+            None,
+            ImportMode::Strict,
+        )
+        .unwrap();
+
+        let mut conn = establish_connection(&db_path).unwrap();
+        assert_eq!(
+            get_words_containing("ath", &mut conn).unwrap(),
+            ["cath", "gath"]
+        );
+        assert_eq!(get_words_containing("awn", &mut conn).unwrap(), ["iawn"]);
+        let keys: Vec<(String, Option<String>)> = crate::schema::lecsicon::table
+            .select((
+                crate::schema::lecsicon::word,
+                crate::schema::lecsicon::search_key,
+            ))
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(keys.len(), 7);
+        for (word, key) in keys {
+            assert_eq!(key, Some(search_key(&word)));
+        }
+        let found = search_entries("gath", SearchMode::Folded, &mut conn).unwrap();
+        assert_eq!(word_ids(&found), [("gath", 101)]);
+        drop(conn);
+        std::fs::remove_file(&db_path).unwrap();
+    }
+
+    #[test]
+    fn failed_import_leaves_no_file() {
+        let db_path =
+            std::env::temp_dir().join(format!("lecsicon-failed-import-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&db_path);
+        let result = save_data_to_sqlite_db(
+            LecsiconReader::new(FIXTURE.as_bytes()),
+            &db_path,
+            None,
+            ImportMode::Strict,
+        );
+        assert!(result.is_err());
+        assert!(!db_path.exists());
+
+        // an existing database is kept as it was
+        save_data_to_sqlite_db(
+            LecsiconReader::new("cath\tcath\tNOUN\tNumber=Sing\n".as_bytes()),
+            &db_path,
+            None,
+            ImportMode::Strict,
+        )
+        .unwrap();
+        let result = save_data_to_sqlite_db(
+            LecsiconReader::new(FIXTURE.as_bytes()),
+            &db_path,
+            None,
+            ImportMode::Strict,
+        );
+        assert!(result.is_err());
+        let mut conn = establish_connection(&db_path).unwrap();
+        assert_eq!(get_words_by_prefix("", &mut conn).unwrap(), ["cath"]);
+        drop(conn);
+        std::fs::remove_file(&db_path).unwrap();
+    }
+
+    #[test]
+    fn writes_through_a_read_only_connection_fail_with_read_only() {
+        let db_path =
+            std::env::temp_dir().join(format!("lecsicon-read-only-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&db_path);
+        save_data_to_sqlite_db(
+            LecsiconReader::new("cath\tcath\tNOUN\tNumber=Sing\n".as_bytes()),
+            &db_path,
+            None,
+            ImportMode::Strict,
+        )
+        .unwrap();
+        for mode in [OpenMode::ReadOnly, OpenMode::Immutable] {
+            let mut conn = establish_connection_with_mode(&db_path, mode).unwrap();
+            let result = save_data_to_connection(
+                LecsiconReader::new("gath\tcath\tNOUN\tNumber=Sing\n".as_bytes()),
+                &mut conn,
+                None,
+                ImportMode::Strict,
+            );
+            assert!(matches!(result, Err(LecsiconError::ReadOnly)), "{mode:?}");
+            assert_eq!(get_words_by_prefix("", &mut conn).unwrap(), ["cath"]);
+        }
+        std::fs::remove_file(&db_path).unwrap();
+    }
+
+    #[test]
+    fn lexicon_clones_share_an_in_memory_database_across_threads() {
+        let lexicon = Lexicon::open(Path::new(MEMORY_DB)).unwrap();
+        save_data_to_connection(
+            LecsiconReader::new(FIXTURE.as_bytes()),
+            &mut lexicon.connection().unwrap(),
+            None,
+            ImportMode::Lenient,
+        )
+        .unwrap();
+        let anchor = Arc::downgrade(lexicon._anchor.as_ref().unwrap());
+        let clone = lexicon.clone();
+        drop(lexicon);
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let lexicon = clone.clone();
+                std::thread::spawn(move || {
+                    let entries = lexicon.search("cath").unwrap();
+                    let related = lexicon.related(entries[0].clone()).unwrap();
+                    (lexicon.suggest("cath").unwrap(), related.len())
+                })
+            })
+            .collect();
+        for thread in threads {
+            let (words, related) = thread.join().unwrap();
+            assert_eq!(words, ["cath", "cathod"]);
+            assert_eq!(related, 4);
+        }
+        // the threads' clones are gone, but this one still holds the data
+        assert!(anchor.upgrade().is_some());
+        assert_eq!(
+            word_ids(&clone.search_with_mode("ddwr", SearchMode::Folded).unwrap()),
+            [("ddŵr", 101)]
+        );
+        drop(clone);
+        assert!(anchor.upgrade().is_none());
+    }
+
+    #[test]
+    fn new_memory_database_is_writable_only() {
+        for mode in [OpenMode::ReadOnly, OpenMode::Immutable] {
+            assert!(matches!(
+                establish_connection_with_mode(Path::new(MEMORY_DB), mode),
+                Err(LecsiconError::InvalidOpenMode(_))
+            ));
+        }
+        for mode in [OpenMode::ReadWrite, OpenMode::Memory] {
+            assert!(establish_connection_with_mode(Path::new(MEMORY_DB), mode).is_ok());
+        }
+    }
+
+    #[test]
+    fn prefixes_match_case_sensitively_unless_folded() {
+        let mut conn = import(FIXTURE, ImportMode::Lenient);
+        assert_eq!(
+            get_words_by_prefix("ca", &mut conn).unwrap(),
+            ["cath", "cathod"]
+        );
+        assert_eq!(get_words_by_prefix("Ca", &mut conn).unwrap(), ["Caerdydd"]);
+        assert_eq!(
+            get_words_by_folded_prefix("ca", &mut conn).unwrap(),
+            ["Caerdydd", "cath", "cathod"]
+        );
+    }
+
+    #[test]
+    fn folded_search_ignores_accents_and_ranks_the_word_as_typed_first() {
+        let mut conn = import(FIXTURE, ImportMode::Lenient);
+        assert!(matches!(
+            search_entries("dwr", SearchMode::Exact, &mut conn),
+            Err(LecsiconError::NotFound(_))
+        ));
+        let found = search_entries("dwr", SearchMode::Folded, &mut conn).unwrap();
+        assert_eq!(word_ids(&found), [("dŵr", 101)]);
+
+        save_data_to_connection(
+            LecsiconReader::new(
+                "Dŵr\tDŵr\tPROPN\t\ndwr\tdwr\tNOUN\t\nDwr\tDwr\tPROPN\t\n".as_bytes(),
+            ),
+            &mut conn,
+            None,
+            ImportMode::Strict,
+        )
+        .unwrap();
+        let mut words = |query: &str| -> Vec<String> {
+            search_entries(query, SearchMode::Folded, &mut conn)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.word)
+                .collect()
+        };
+        assert_eq!(words("dwr"), ["dwr", "Dwr", "Dŵr", "dŵr"]);
+        assert_eq!(words("dŵr"), ["dŵr", "Dŵr", "Dwr", "dwr"]);
+        assert_eq!(words("DWR"), ["Dwr", "dwr", "Dŵr", "dŵr"]);
+    }
+
+    #[test]
+    fn unknown_feature_codes_fail_as_undecodable_rows() {
+        let mut conn = import(FIXTURE, ImportMode::Lenient);
+        // the code tables would refuse the write below
+        conn.batch_execute(
+            "PRAGMA foreign_keys = OFF; UPDATE lecsicon SET person = 9 WHERE word = 'cath';",
+        )
+        .unwrap();
+        assert!(matches!(
+            search_entries("cath", SearchMode::Exact, &mut conn),
+            Err(LecsiconError::UndecodableRow {
+                column: "person",
+                value: 9
+            })
+        ));
+        assert_eq!(
+            search_entries("cathod", SearchMode::Exact, &mut conn)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn schema_version_counts_the_bundled_migrations() {
+        let mut conn = establish_connection(Path::new(MEMORY_DB)).unwrap();
+        let bundled = MigrationSource::<Sqlite>::migrations(&MIGRATIONS).unwrap();
+        assert_eq!(bundled.len() as i32, SCHEMA_VERSION);
+        assert_eq!(get_schema_version(&mut conn).unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn trigram_index_follows_imports() {
+        let mut conn = import(FIXTURE, ImportMode::Lenient);
+        assert_eq!(
+            get_words_containing("ath", &mut conn).unwrap(),
+            ["cath", "cathod", "chath", "gath", "nghath"]
+        );
+        // the trigger is back, so rows added later are indexed as they go
+        save_data_to_connection(
+            LecsiconReader::new("cathau\tcath\tNOUN\tNumber=Plur\n".as_bytes()),
+            &mut conn,
+            None,
+            ImportMode::Strict,
+        )
+        .unwrap();
+        assert!(get_words_containing("athau", &mut conn)
+            .unwrap()
+            .contains(&"cathau".to_string()));
+    }
+
+    #[cfg(feature = "snapshot")]
+    #[test]
+    fn snapshot_answers_as_sqlite_does() {
+        let mut conn = import(FIXTURE, ImportMode::Lenient);
+        let mut bytes = vec![];
+        write_sqlite_db_as_snapshot(&mut conn, &mut bytes).unwrap();
+        let snapshot = LecsiconSnapshot::from_bytes(&bytes).unwrap();
+        let text =
+            |result: Result<Vec<LecsiconEntry>, LecsiconError>| result.map_err(|e| e.to_string());
+
+        let words = get_words_by_prefix("", &mut conn).unwrap();
+        assert_eq!(snapshot.suggest("").unwrap(), words);
+        for word in &words {
+            let entries = search_entries(word, SearchMode::Exact, &mut conn).unwrap();
+            assert_eq!(snapshot.search(word).unwrap(), entries);
+            for (end, _) in word.char_indices().skip(1) {
+                let prefix = &word[..end];
+                assert_eq!(
+                    snapshot.suggest(prefix).unwrap(),
+                    get_words_by_prefix(prefix, &mut conn).unwrap()
+                );
+            }
+            for entry in entries {
+                assert_eq!(
+                    text(snapshot.related(&entry)),
+                    text(get_related_entries(entry, &mut conn))
+                );
+            }
+        }
+        assert!(snapshot.search("nothere").is_err());
+    }
+}
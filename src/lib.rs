@@ -1,72 +1,348 @@
 pub mod definitions;
 mod implementations;
+#[cfg(feature = "sqlite")]
 mod schema;
 
-use std::boxed::Box;
-use std::error::Error;
 use std::path::Path;
-use std::rc::Rc;
 use std::result::Result;
 
+/// Opens the database read-write, applying any migrations it is missing. A
+/// database with migrations this version doesn't know is refused, and
+/// `":memory:"` opens a new, empty in-memory database.
+#[cfg(feature = "sqlite")]
 pub fn connection(
     db_file_path: &Path,
-) -> Result<Rc<diesel::SqliteConnection>, Box<(dyn Error + 'static)>> {
+) -> Result<diesel::SqliteConnection, definitions::LecsiconError> {
     implementations::establish_connection(db_file_path)
 }
 
-pub fn save_csv_as_sqlite_db(csv_file_path: &Path, db_file_path: &Path) {
-    println!("loading data from csv...");
-    if let Some(data) = implementations::load_text_lecsicon(csv_file_path) {
-        if let Ok(mut db_connection) = implementations::establish_connection(db_file_path) {
-            println!("converting loaded data to sqlite-ready format...");
-            let sqlite_ready_data = implementations::convert_csv_to_sqlite_format(data);
-            println!("saving data to the database...");
-            if implementations::save_data_to_sqlite_db(sqlite_ready_data, db_file_path) {
-                println!("attempting to search...");
-                if search("meddwl", Rc::get_mut(&mut db_connection).unwrap()).is_some()
-                    && search("iawn", Rc::get_mut(&mut db_connection).unwrap()).is_some()
-                    && search("yr", Rc::get_mut(&mut db_connection).unwrap()).is_some()
-                {
-                    println!("data confirmed in db");
-                } else {
-                    println!("no search results, data not confirmed in db");
-                }
-            } else {
-                println!("saving failed");
-            }
-        }
-    } else {
-        println!("no csv data");
-    }
+/// Opens the database in the given `OpenMode`. `OpenMode::Memory` copies the
+/// file into memory with SQLite's backup API, and writes through a
+/// `ReadOnly` or `Immutable` connection fail with `LecsiconError::ReadOnly`.
+/// A new in-memory database can only be opened `ReadWrite` or `Memory`.
+#[cfg(feature = "sqlite")]
+pub fn connection_with_mode(
+    db_file_path: &Path,
+    mode: definitions::OpenMode,
+) -> Result<diesel::SqliteConnection, definitions::LecsiconError> {
+    implementations::establish_connection_with_mode(db_file_path, mode)
+}
+
+/// As `connection`, but creates the database file if it doesn't exist.
+#[cfg(feature = "sqlite")]
+pub fn create_or_open(
+    db_file_path: &Path,
+) -> Result<diesel::SqliteConnection, definitions::LecsiconError> {
+    implementations::create_or_open(db_file_path)
+}
+
+/// Opens a `Lexicon`, a pool of connections in the given mode that can be
+/// cloned and shared between threads.
+#[cfg(feature = "sqlite")]
+pub fn open_lexicon(
+    db_file_path: &Path,
+    mode: definitions::OpenMode,
+) -> Result<definitions::Lexicon, definitions::LecsiconError> {
+    definitions::Lexicon::open_with_mode(db_file_path, mode)
+}
+
+/// The number of migrations applied to the database.
+#[cfg(feature = "sqlite")]
+pub fn schema_version(
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<i32, definitions::LecsiconError> {
+    implementations::get_schema_version(db_connection)
+}
+
+/// Imports a lecsicon CSV into the database, creating it if needed, and
+/// stops at the first line it cannot parse. The import is one transaction:
+/// if it fails nothing is written, and a database file it created is
+/// removed again.
+#[cfg(feature = "sqlite")]
+pub fn save_csv_as_sqlite_db(
+    csv_file_path: &Path,
+    db_file_path: &Path,
+) -> Result<definitions::ImportSummary, definitions::LecsiconError> {
+    save_csv_as_sqlite_db_with_mode(csv_file_path, db_file_path, definitions::ImportMode::Strict)
+}
+
+/// As `save_csv_as_sqlite_db`, with lenient imports keeping the lines they
+/// cannot parse aside for `rejects`.
+#[cfg(feature = "sqlite")]
+pub fn save_csv_as_sqlite_db_with_mode(
+    csv_file_path: &Path,
+    db_file_path: &Path,
+    mode: definitions::ImportMode,
+) -> Result<definitions::ImportSummary, definitions::LecsiconError> {
+    let entries = read_csv(csv_file_path)?;
+    implementations::save_data_to_sqlite_db(entries, db_file_path, Some(csv_file_path), mode)
+}
+
+/// Reads a lecsicon CSV one entry at a time.
+pub fn read_csv(
+    csv_file_path: &Path,
+) -> Result<definitions::LecsiconReader<std::fs::File>, definitions::LecsiconError> {
+    definitions::LecsiconReader::open(csv_file_path)
+}
+
+/// As `save_csv_as_sqlite_db`, for entries from any source.
+#[cfg(feature = "sqlite")]
+pub fn save_entries_as_sqlite_db<I>(
+    entries: I,
+    db_file_path: &Path,
+    mode: definitions::ImportMode,
+) -> Result<definitions::ImportSummary, definitions::LecsiconError>
+where
+    I: IntoIterator<Item = Result<definitions::LecsiconEntry, definitions::LecsiconError>>,
+{
+    implementations::save_data_to_sqlite_db(entries, db_file_path, None, mode)
+}
+
+/// Adds entries through an open connection, such as one to an in-memory
+/// database.
+#[cfg(feature = "sqlite")]
+pub fn save_entries_to_connection<I>(
+    entries: I,
+    db_connection: &mut diesel::SqliteConnection,
+    mode: definitions::ImportMode,
+) -> Result<definitions::ImportSummary, definitions::LecsiconError>
+where
+    I: IntoIterator<Item = Result<definitions::LecsiconEntry, definitions::LecsiconError>>,
+{
+    implementations::save_data_to_connection(entries, db_connection, None, mode)
+}
+
+/// The entries a CSV would add, remove and change, without writing them.
+#[cfg(feature = "sqlite")]
+pub fn compare_csv_with_sqlite_db(
+    csv_file_path: &Path,
+    db_file_path: &Path,
+    mode: definitions::ImportMode,
+) -> Result<definitions::LecsiconChanges, definitions::LecsiconError> {
+    let entries = read_csv(csv_file_path)?;
+    implementations::compare_data_with_sqlite_db(entries, db_file_path, mode)
+}
+
+/// Brings the database in line with a newer CSV. Entries whose analysis is
+/// unchanged keep their ids.
+#[cfg(feature = "sqlite")]
+pub fn update_sqlite_db_from_csv(
+    csv_file_path: &Path,
+    db_file_path: &Path,
+    mode: definitions::ImportMode,
+) -> Result<definitions::LecsiconChanges, definitions::LecsiconError> {
+    let entries = read_csv(csv_file_path)?;
+    implementations::update_sqlite_db(entries, db_file_path, Some(csv_file_path), mode)
+}
+
+/// The lines a lenient import set aside, with the reason for each.
+#[cfg(feature = "sqlite")]
+pub fn rejects(
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<Vec<definitions::LecsiconReject>, definitions::LecsiconError> {
+    Ok(implementations::get_rejects(db_connection)?)
+}
+
+/// Writes the matching entries as a lecsicon CSV in the order they were
+/// imported. Unfiltered, this gives back the imported file byte for byte
+/// when it had LF line endings, a final newline and no blank lines.
+#[cfg(feature = "sqlite")]
+pub fn export_sqlite_db_as_csv<W: std::io::Write>(
+    db_connection: &mut diesel::SqliteConnection,
+    out: &mut W,
+    filter: &definitions::ExportFilter,
+) -> Result<usize, definitions::LecsiconError> {
+    implementations::export_sqlite_db_as_csv(db_connection, out, filter)
+}
+
+/// Writes entries as lecsicon CSV lines.
+pub fn write_entries_as_csv<I, W>(
+    entries: I,
+    out: &mut W,
+) -> Result<usize, definitions::LecsiconError>
+where
+    I: IntoIterator<Item = definitions::LecsiconEntry>,
+    W: std::io::Write,
+{
+    implementations::write_entries_as_csv(entries, out)
+}
+
+/// Writes the matching entries as JSON Lines, in word order.
+#[cfg(all(feature = "sqlite", feature = "serde"))]
+pub fn export_sqlite_db_as_jsonl<W: std::io::Write>(
+    db_connection: &mut diesel::SqliteConnection,
+    out: &mut W,
+    filter: &definitions::ExportFilter,
+) -> Result<usize, definitions::LecsiconError> {
+    implementations::export_sqlite_db_as_jsonl(db_connection, out, filter)
+}
+
+/// Writes `lemma\tform\tFEATS` triples in the UniMorph schema. Features with
+/// no UniMorph equivalent, such as mutation, are left out and counted in the
+/// returned `UnimorphReport`.
+#[cfg(feature = "sqlite")]
+pub fn export_sqlite_db_as_unimorph<W: std::io::Write>(
+    db_connection: &mut diesel::SqliteConnection,
+    out: &mut W,
+    filter: &definitions::ExportFilter,
+) -> Result<definitions::UnimorphReport, definitions::LecsiconError> {
+    implementations::export_sqlite_db_as_unimorph(db_connection, out, filter)
 }
 
+/// Writes a Hunspell .dic/.aff pair of every word, with the Welsh mutations
+/// as prefix rules. `SqliteSearcher::with_hunspell` can use it for spelling
+/// suggestions.
+#[cfg(feature = "sqlite")]
+pub fn export_sqlite_db_as_hunspell<D: std::io::Write, A: std::io::Write>(
+    db_connection: &mut diesel::SqliteConnection,
+    dic: &mut D,
+    aff: &mut A,
+) -> Result<definitions::HunspellReport, definitions::LecsiconError> {
+    implementations::export_sqlite_db_as_hunspell(db_connection, dic, aff)
+}
+
+/// Compiles the database into a snapshot that `LecsiconSnapshot::from_bytes`
+/// reads in place, answering `search`, `suggest` and `related` as the
+/// database does.
+#[cfg(all(feature = "sqlite", feature = "snapshot"))]
+pub fn write_sqlite_db_as_snapshot<W: std::io::Write>(
+    db_connection: &mut diesel::SqliteConnection,
+    out: &mut W,
+) -> Result<usize, definitions::LecsiconError> {
+    implementations::write_sqlite_db_as_snapshot(db_connection, out)
+}
+
+/// Where the most recent import or update came from.
+#[cfg(feature = "sqlite")]
+pub fn import_metadata(
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<Option<definitions::ImportMetadata>, definitions::LecsiconError> {
+    Ok(implementations::get_latest_import(db_connection)?)
+}
+
+/// Every import and update, newest first.
+#[cfg(feature = "sqlite")]
+pub fn import_history(
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<Vec<definitions::ImportMetadata>, definitions::LecsiconError> {
+    Ok(implementations::get_import_history(db_connection)?)
+}
+
+/// The entries for the word exactly as typed.
+#[cfg(feature = "sqlite")]
 pub fn search(
     word: &str,
     db_connection: &mut diesel::SqliteConnection,
-) -> Option<Vec<definitions::LecsiconEntry>> {
-    let results = implementations::get_lecsicon_entries_by_word(word, db_connection)
-        .into_iter()
-        .map(implementations::convert_sqlite_to_text)
-        .collect::<Vec<definitions::LecsiconEntry>>();
-    if results.is_empty() {
-        None
-    } else {
-        Some(results)
-    }
+) -> Result<Vec<definitions::LecsiconEntry>, definitions::LecsiconError> {
+    search_with_mode(word, definitions::SearchMode::Exact, db_connection)
 }
 
+/// The entries for the word. `SearchMode::Folded` ignores case and the
+/// circumflex, grave, acute and diaeresis, so `dwr` finds `dŵr`, and lists
+/// the word as typed first, then other cases, then other accents.
+#[cfg(feature = "sqlite")]
+pub fn search_with_mode(
+    word: &str,
+    mode: definitions::SearchMode,
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<Vec<definitions::LecsiconEntry>, definitions::LecsiconError> {
+    implementations::search_entries(word, mode, db_connection)
+}
+
+/// Entries with a UD feature value, such as `Mutation`, `SM`. Features with
+/// no typed field on `LecsiconEntry`, such as `Foreign=Yes`, are found in
+/// its `other_features`.
+#[cfg(feature = "sqlite")]
+pub fn search_by_feature(
+    name: &str,
+    value: &str,
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<Vec<definitions::LecsiconEntry>, definitions::LecsiconError> {
+    implementations::get_entries_by_feature(name, value, db_connection)
+}
+
+/// Words starting with the prefix, in byte order. The match is
+/// case-sensitive, so `ca` does not suggest `Caerdydd`.
+#[cfg(feature = "sqlite")]
+pub fn suggest(
+    prefix: &str,
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<Vec<String>, definitions::LecsiconError> {
+    Ok(implementations::get_words_by_prefix(prefix, db_connection)?)
+}
+
+/// Every lemma and tag in lemma order, with the number of forms each has.
+#[cfg(feature = "sqlite")]
+pub fn lemmas(
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<Vec<definitions::Lemma>, definitions::LecsiconError> {
+    implementations::get_lemmas(db_connection)
+}
+
+/// The lemma with the given id.
+#[cfg(feature = "sqlite")]
+pub fn lemma(
+    lemma_id: i32,
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<definitions::Lemma, definitions::LecsiconError> {
+    implementations::get_lemma(lemma_id, db_connection)
+}
+
+/// The entries inflected from the lemma with the given id.
+#[cfg(feature = "sqlite")]
+pub fn lemma_forms(
+    lemma_id: i32,
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<Vec<definitions::LecsiconEntry>, definitions::LecsiconError> {
+    implementations::get_lemma_forms(lemma_id, db_connection)
+}
+
+/// Words with the fragment anywhere in them, ignoring case, so `ydd` finds
+/// `Caerdydd`. Fragments of three or more characters use the trigram index.
+#[cfg(feature = "sqlite")]
+pub fn words_containing(
+    fragment: &str,
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<Vec<String>, definitions::LecsiconError> {
+    implementations::get_words_containing(fragment, db_connection)
+}
+
+/// Forms of the lemma spelt as the entry's word, with its tag, in word
+/// order. A conjugated verb gets only those with its mood, tense, number and
+/// person.
+#[cfg(feature = "sqlite")]
 pub fn related(
     entry: definitions::LecsiconEntry,
     db_connection: &mut diesel::SqliteConnection,
-) -> Option<Vec<definitions::LecsiconEntry>> {
+) -> Result<Vec<definitions::LecsiconEntry>, definitions::LecsiconError> {
     implementations::get_related_entries(entry, db_connection)
 }
 
+/// `search`, as a JSON array.
+#[cfg(all(feature = "sqlite", feature = "serde"))]
+pub fn search_as_json(
+    word: &str,
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<String, definitions::LecsiconError> {
+    Ok(serde_json::to_string(&search(word, db_connection)?)?)
+}
+
+/// `related`, as a JSON array.
+#[cfg(all(feature = "sqlite", feature = "serde"))]
+pub fn related_as_json(
+    entry: definitions::LecsiconEntry,
+    db_connection: &mut diesel::SqliteConnection,
+) -> Result<String, definitions::LecsiconError> {
+    Ok(serde_json::to_string(&related(entry, db_connection)?)?)
+}
+
+/// An `inquire` prompt that searches the database as the user types.
+#[cfg(feature = "sqlite")]
 pub fn text_prompt(db_file_path: &Path, recent_searches: Vec<String>) -> Option<inquire::Text<'_>> {
-    if let Ok(db_connection) = implementations::establish_connection(db_file_path) {
+    if let Ok(lexicon) = definitions::Lexicon::open(db_file_path) {
         Some(
             inquire::Text::new("Chwilio: ").with_autocomplete(definitions::SqliteSearcher::new(
-                db_connection,
+                lexicon,
                 recent_searches.into_iter().rev().collect::<Vec<String>>(),
             )),
         )
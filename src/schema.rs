@@ -1,30 +1,249 @@
 // @generated automatically by Diesel CLI.
 
 diesel::table! {
+    adposition_type_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    definite_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    degree_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    gender_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::definitions::sql_types::*;
+
     lecsicon (id, word) {
         id -> Integer,
         word -> Text,
-        lemma -> Nullable<Text>,
-        tag -> Nullable<Integer>,
-        adposition_type -> Nullable<Bool>,
-        definite -> Nullable<Bool>,
-        degree -> Nullable<Integer>,
-        gender -> Nullable<Integer>,
-        mood -> Nullable<Integer>,
-        mutation -> Nullable<Integer>,
-        name_type -> Nullable<Integer>,
-        number -> Nullable<Integer>,
-        number_form -> Nullable<Bool>,
-        number_type -> Nullable<Integer>,
-        person -> Nullable<Integer>,
-        polarity -> Nullable<Bool>,
-        polite -> Nullable<Integer>,
-        position -> Nullable<Bool>,
-        possessive -> Nullable<Bool>,
-        pronoun_type -> Nullable<Integer>,
-        relative -> Nullable<Bool>,
-        style -> Nullable<Integer>,
-        tense -> Nullable<Integer>,
-        verb_form -> Nullable<Bool>,
+        tag -> Nullable<TagCode>,
+        adposition_type -> Nullable<AdpositionTypeCode>,
+        definite -> Nullable<DefiniteCode>,
+        degree -> Nullable<DegreeCode>,
+        gender -> Nullable<GenderCode>,
+        mood -> Nullable<MoodCode>,
+        mutation -> Nullable<MutationCode>,
+        name_type -> Nullable<NameTypeCode>,
+        number -> Nullable<NumberCode>,
+        number_form -> Nullable<NumberFormCode>,
+        number_type -> Nullable<NumberTypeCode>,
+        person -> Nullable<PersonCode>,
+        polarity -> Nullable<PolarityCode>,
+        polite -> Nullable<PoliteCode>,
+        position -> Nullable<PositionCode>,
+        possessive -> Nullable<PossessiveCode>,
+        pronoun_type -> Nullable<PronounTypeCode>,
+        relative -> Nullable<RelativeCode>,
+        style -> Nullable<StyleCode>,
+        tense -> Nullable<TenseCode>,
+        verb_form -> Nullable<VerbFormCode>,
+        source_line -> Nullable<Integer>,
+        raw_features -> Nullable<Text>,
+        extra_fields -> Nullable<Text>,
+        other_features -> Nullable<Text>,
+        lemma_id -> Nullable<Integer>,
+        search_key -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    lecsicon_metadata (id) {
+        id -> Integer,
+        source_path -> Text,
+        source_sha256 -> Text,
+        rows_read -> Integer,
+        rows_total -> Integer,
+        imported_at -> Text,
+        crate_version -> Text,
+        schema_version -> Integer,
+        rows_rejected -> Integer,
+    }
+}
+
+diesel::table! {
+    lecsicon_rejects (id) {
+        id -> Integer,
+        line -> Integer,
+        raw_line -> Text,
+        reason -> Text,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use crate::definitions::sql_types::*;
+
+    lemmas (id) {
+        id -> Integer,
+        lemma -> Text,
+        tag -> Nullable<TagCode>,
+    }
+}
+
+diesel::table! {
+    mood_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    mutation_codes (code) {
+        code -> Integer,
+        label -> Text,
     }
 }
+
+diesel::table! {
+    name_type_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    number_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    number_form_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    number_type_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    person_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    polarity_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    polite_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    position_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    possessive_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    pronoun_type_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    relative_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    style_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    tag_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    tense_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::table! {
+    verb_form_codes (code) {
+        code -> Integer,
+        label -> Text,
+    }
+}
+
+diesel::joinable!(lecsicon -> lemmas (lemma_id));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    adposition_type_codes,
+    definite_codes,
+    degree_codes,
+    gender_codes,
+    lecsicon,
+    lecsicon_metadata,
+    lecsicon_rejects,
+    lemmas,
+    mood_codes,
+    mutation_codes,
+    name_type_codes,
+    number_codes,
+    number_form_codes,
+    number_type_codes,
+    person_codes,
+    polarity_codes,
+    polite_codes,
+    position_codes,
+    possessive_codes,
+    pronoun_type_codes,
+    relative_codes,
+    style_codes,
+    tag_codes,
+    tense_codes,
+    verb_form_codes,
+);